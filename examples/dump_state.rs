@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Prints what the first Launch keyboard found reports about itself, and its
+//! lighting as an exported profile.
+
+use cosmic_applet_launch_control::launch::Launch;
+use cosmic_applet_launch_control::profile::Profile;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut launch = Launch::try_new()?;
+    println!("board: {}", launch.board());
+    println!("firmware: {}", launch.version());
+    println!("max brightness: {}", launch.max_brightness());
+    if let Some(usb) = launch.usb() {
+        println!("usb: {} ({})", usb.port_path, usb.speed_label());
+    }
+
+    let snapshot = launch.snapshot()?;
+    println!("mode: {}", snapshot.mode);
+    println!("speed: {}", snapshot.speed);
+    println!("brightness: {}", snapshot.brightness);
+    println!("color: {} ({})", snapshot.color, snapshot.color.to_hex());
+
+    println!("{}", Profile::from_launch("Current", &launch).export()?);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Switches the first Launch keyboard found to solid color mode, keeping
+//! its speed, and prints the color it shows.
+//!
+//! Pass a hue and saturation from 0 to 255 to set the color as well, e.g.
+//! `cargo run --example set_solid_color -- 170 255` for blue.
+
+use cosmic_applet_launch_control::launch::{Color, Launch, LedMode};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut launch = Launch::try_new()?;
+    launch.set_led_mode(LedMode::SolidColor, launch.current_speed())?;
+    if let [hue, saturation] = args.as_slice() {
+        launch.set_color(Color {
+            hue: hue.parse()?,
+            saturation: saturation.parse()?,
+        })?;
+    }
+    println!("{} shows {}", launch.board(), launch.color().to_hex());
+    Ok(())
+}
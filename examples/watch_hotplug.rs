@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Prints Launch keyboards as they're plugged in and out.
+
+use cosmic_applet_launch_control::bus::{Event, EventBus};
+use cosmic_applet_launch_control::device_listener::DeviceListener;
+
+#[tokio::main]
+async fn main() {
+    let bus = EventBus::default();
+    let mut events = bus.subscribe();
+    let print = async move {
+        while let Ok(event) = events.recv().await {
+            match event {
+                Event::DeviceConnected(info) if info.is_launch() => {
+                    println!("connected {:04x}:{:04x}", info.vid, info.pid);
+                }
+                Event::DeviceDisconnected(info) if info.is_launch() => {
+                    println!("disconnected {:04x}:{:04x}", info.vid, info.pid);
+                }
+                _ => {}
+            }
+        }
+    };
+    tokio::join!(DeviceListener::new().run(bus), print);
+}
@@ -1 +1,178 @@
-example-row = Example row
+no-device = No Launch keyboard connected
+search-modes = Search modes
+no-matching-modes = No matching modes
+most-used-first = Most used first
+tab-modes = Modes
+tab-profiles = Profiles
+tab-rules = Rules
+apply = Apply
+profile-name = Profile name
+save-current = Save current
+no-profiles = No saved profiles
+search-profiles = Search profiles
+no-rules = No rules yet
+rule-summary = { $trigger } → { $profile } (priority { $priority })
+rule-trigger = When
+rule-profile = Apply profile
+rule-param-time-range = HH:MM-HH:MM
+rule-param-app-id = Application ID
+add-rule = Add rule
+trigger-device-connected = Keyboard connected
+trigger-app-focused = Application focused
+trigger-time-range = Time of day
+trigger-on-battery = On battery
+trigger-locked = Screen locked
+explain-rules = Why is my keyboard this color?
+explain-no-rule = No rule matches right now, so the lighting is whatever was set last.
+explain-winner = "{ $trigger }" (priority { $priority }) selects profile "{ $profile }".
+explain-overridden = "{ $trigger }" (priority { $priority }) also matches but is overridden.
+tab-device = Device
+board = Board
+firmware = Firmware
+refresh = Refresh
+changes-since-login = Changes since login
+no-changes = Nothing changed
+change-mode = Mode: { $from } → { $to }
+change-speed = Speed: { $from } → { $to }
+change-brightness = Brightness: { $from } → { $to }
+change-color = Color: { $from } → { $to }
+firmware-quirks = Firmware quirks
+firmware-quirks-description = Settings the keyboard applied differently than requested (requested → applied)
+speed = Speed
+brightness = Brightness
+hue = Hue
+saturation = Saturation
+presets = Presets
+rule-days = On
+days-every = Every day
+days-weekdays = Weekdays
+days-weekends = Weekends
+skip-rule = Skip
+resume-rule = Skipped until { $until }
+do-not-disturb = Do Not Disturb
+trigger-do-not-disturb = Do Not Disturb is on
+trigger-microphone-in-use = Microphone in use
+trigger-camera-in-use = Camera in use
+trigger-script = Script reports a value
+rule-param-script = Command, e.g. notmuch count tag:unread
+tab-diagnostics = Diagnostics
+ec-latency = EC round-trip latency
+measure-latency = Measure latency
+measuring-latency = Measuring… { $done }/{ $total }
+latency-max = Maximum
+ec-stress = EC link stress test
+seconds = { $seconds } s
+start-stress = Start
+stressing = Running… { $cycles } cycles
+stress-cycles = Read/write cycles
+stress-retries = Retries
+stress-errors = Errors
+led-power = LED power (estimate)
+led-power-watts = ≈ { $watts } W
+usb-connection = USB connection
+usb-port = Port
+usb-speed = Speed
+usb-hubs = Hubs
+usb-unknown = The USB connection couldn't be read from sysfs.
+usb-hub-hint = The keyboard is connected through a hub. If it keeps disconnecting, try a port on the computer itself.
+usb-low-speed = Low speed (1.5 Mbit/s)
+usb-full-speed = Full speed (12 Mbit/s)
+usb-high-speed = High speed (480 Mbit/s)
+usb-super-speed = SuperSpeed (5 Gbit/s or more)
+event-log = Event log
+event-log-empty = No connection events or errors so far.
+slider-step = Slider step
+color = Color
+copy-color = Copy color
+scenes = Scenes
+no-scenes = No scenes saved yet.
+scene-name = Scene name
+scene-profile = Profile to apply
+save-scene = Save scene
+scene-description = A scene remembers which rules are turned on right now, together with the chosen profile.
+activate = Activate
+metrics = Counters
+metrics-commands = EC commands
+metrics-errors = EC errors
+metrics-reconnects = Reconnects
+copy-metrics = Copy as Prometheus text
+task-restarted = The { $task } stopped unexpectedly and is being restarted. See the diagnostics tab for details.
+exit-state = When the panel restarts
+exit-keep = Leave as is
+logout-state = When logging out or shutting down
+device-default-profile = Device default ({ $board })
+compare-profiles = Compare
+live-keyboard = Live keyboard
+profiles-identical = No differences.
+section-search = Mode search
+section-modes = Mode list
+customize = Customize
+popup-width = Popup width
+pixels = { $pixels } px
+done = Done
+timers = Timers
+timer-minutes = { $minutes } min
+timer-left = Flashes in { $time }
+timer-notification = Notify when a timer ends
+timer-expired = Timer finished
+timer-expired-body = Your Launch Control timer has run out.
+cleaning-mode = Cleaning mode
+cleaning-description = Ignores key presses for a while so the keyboard can be wiped. Use the mouse to stop early.
+cleaning-left = Keys blocked, { $seconds } s left
+start = Start
+stop = Stop
+child-lock = Child lock
+lock-profile = Lighting while locked
+pending-changes = The keyboard is disconnected. Your last change will apply when it reconnects.
+staged-editing = Apply changes manually
+staged-changes = Changes not applied yet
+discard = Discard
+max-brightness = Maximum brightness
+quiet-hours = Quiet hours
+quiet-hours-active = Lighting is off for quiet hours
+override = Override
+guest-mode = Guest mode, changes aren't kept
+demo-mode = Demo mode, cycles through all modes
+playlists = Playlists
+no-playlists = No playlists yet. Pick a profile and a length, then add it to a new playlist by name.
+playlist-name = Playlist name
+playlist-entry = { $profile } ({ $minutes } min)
+add-to-playlist = Add to playlist
+play = Play
+speed-slow = Slow
+speed-medium = Medium
+speed-fast = Fast
+applied-differently = The keyboard didn't apply everything as requested: { $changes }
+history = History
+history-empty = The lighting hasn't changed since the applet started.
+history-manual = Changed by hand
+history-rule = Rule { $rule }
+history-child-lock = Child lock
+history-quiet-hours = Quiet hours
+history-playlist = Playlist { $name }
+history-playlist-stopped = Playlist stopped
+history-demo = Demo mode
+history-timer = Timer
+copy-history = Copy history
+simulate = Simulate a situation
+simulate-time = Time
+simulate-time-now = Now, or HH:MM
+simulate-run = Which rule wins?
+yielded-to = { $name } is using the keyboard. Changes will apply once it quits.
+external-controller = { $names } may also be changing the keyboard lighting.
+yield-to = Leave the keyboard to { $name }
+advanced = Advanced…
+cancel = Cancel
+delete = Delete
+save = Save
+confirm-delete-profile = Delete the profile “{ $name }”? Rules using it are deleted too.
+link-health = Link to the keyboard
+link-healthy = Healthy
+link-slow = The keyboard is answering slowly, so changes may lag.
+link-degraded = { $errors ->
+    [one] A command to the keyboard failed recently; changes may lag or not apply.
+   *[other] { $errors } commands to the keyboard failed recently; changes may lag or not apply.
+}
+save-to-keyboard = Save to keyboard
+confirm-save-to-keyboard = Store the current lighting on the keyboard? It will be used after unplugging and on other computers.
+brightness-cap = Brightness limit
@@ -1 +1,178 @@
-example-row = Voorbeeldregel
+no-device = Geen Launch-toetsenbord verbonden
+search-modes = Modi zoeken
+no-matching-modes = Geen overeenkomende modi
+most-used-first = Meest gebruikt eerst
+tab-modes = Modi
+tab-profiles = Profielen
+tab-rules = Regels
+apply = Toepassen
+profile-name = Profielnaam
+save-current = Huidige opslaan
+no-profiles = Geen opgeslagen profielen
+search-profiles = Profielen zoeken
+no-rules = Nog geen regels
+rule-summary = { $trigger } → { $profile } (prioriteit { $priority })
+rule-trigger = Wanneer
+rule-profile = Profiel toepassen
+rule-param-time-range = UU:MM-UU:MM
+rule-param-app-id = Applicatie-ID
+add-rule = Regel toevoegen
+trigger-device-connected = Toetsenbord verbonden
+trigger-app-focused = Applicatie gefocust
+trigger-time-range = Tijdstip
+trigger-on-battery = Op batterij
+trigger-locked = Scherm vergrendeld
+explain-rules = Waarom heeft mijn toetsenbord deze kleur?
+explain-no-rule = Er is nu geen passende regel, dus de verlichting is wat het laatst is ingesteld.
+explain-winner = "{ $trigger }" (prioriteit { $priority }) kiest profiel "{ $profile }".
+explain-overridden = "{ $trigger }" (prioriteit { $priority }) past ook maar wordt overschreven.
+tab-device = Apparaat
+board = Bord
+firmware = Firmware
+refresh = Vernieuwen
+changes-since-login = Wijzigingen sinds aanmelden
+no-changes = Niets gewijzigd
+change-mode = Modus: { $from } → { $to }
+change-speed = Snelheid: { $from } → { $to }
+change-brightness = Helderheid: { $from } → { $to }
+change-color = Kleur: { $from } → { $to }
+firmware-quirks = Firmware-eigenaardigheden
+firmware-quirks-description = Instellingen die het toetsenbord anders toepaste dan gevraagd (gevraagd → toegepast)
+speed = Snelheid
+brightness = Helderheid
+hue = Tint
+saturation = Verzadiging
+presets = Voorinstellingen
+rule-days = Op
+days-every = Elke dag
+days-weekdays = Doordeweeks
+days-weekends = Weekenden
+skip-rule = Overslaan
+resume-rule = Overgeslagen tot { $until }
+do-not-disturb = Niet storen
+trigger-do-not-disturb = Niet storen staat aan
+trigger-microphone-in-use = Microfoon in gebruik
+trigger-camera-in-use = Camera in gebruik
+trigger-script = Script meldt een waarde
+rule-param-script = Opdracht, bijv. notmuch count tag:unread
+tab-diagnostics = Diagnose
+ec-latency = EC-rondreislatentie
+measure-latency = Latentie meten
+measuring-latency = Meten… { $done }/{ $total }
+latency-max = Maximum
+ec-stress = Belastingstest EC-verbinding
+seconds = { $seconds } s
+start-stress = Starten
+stressing = Bezig… { $cycles } cycli
+stress-cycles = Lees-/schrijfcycli
+stress-retries = Nieuwe pogingen
+stress-errors = Fouten
+led-power = LED-vermogen (schatting)
+led-power-watts = ≈ { $watts } W
+usb-connection = USB-verbinding
+usb-port = Poort
+usb-speed = Snelheid
+usb-hubs = Hubs
+usb-unknown = De USB-verbinding kon niet uit sysfs worden gelezen.
+usb-hub-hint = Het toetsenbord is via een hub aangesloten. Als de verbinding steeds wegvalt, probeer dan een poort op de computer zelf.
+usb-low-speed = Lage snelheid (1,5 Mbit/s)
+usb-full-speed = Volledige snelheid (12 Mbit/s)
+usb-high-speed = Hoge snelheid (480 Mbit/s)
+usb-super-speed = SuperSpeed (5 Gbit/s of meer)
+event-log = Gebeurtenislogboek
+event-log-empty = Nog geen verbindingsgebeurtenissen of fouten.
+slider-step = Stapgrootte schuifregelaar
+color = Kleur
+copy-color = Kleur kopiëren
+scenes = Scènes
+no-scenes = Nog geen scènes opgeslagen.
+scene-name = Naam van scène
+scene-profile = Toe te passen profiel
+save-scene = Scène opslaan
+scene-description = Een scène onthoudt welke regels nu aan staan, samen met het gekozen profiel.
+activate = Activeren
+metrics = Tellers
+metrics-commands = EC-opdrachten
+metrics-errors = EC-fouten
+metrics-reconnects = Herverbindingen
+copy-metrics = Kopiëren als Prometheus-tekst
+task-restarted = De { $task } is onverwacht gestopt en wordt opnieuw gestart. Zie het tabblad Diagnose voor details.
+exit-state = Bij herstarten van het paneel
+exit-keep = Laten zoals het is
+logout-state = Bij afmelden of uitschakelen
+device-default-profile = Standaard van apparaat ({ $board })
+compare-profiles = Vergelijken
+live-keyboard = Toetsenbord nu
+profiles-identical = Geen verschillen.
+section-search = Modi zoeken
+section-modes = Lijst met modi
+customize = Aanpassen
+popup-width = Breedte van pop-up
+pixels = { $pixels } px
+done = Klaar
+timers = Timers
+timer-minutes = { $minutes } min
+timer-left = Knippert over { $time }
+timer-notification = Melding als een timer afloopt
+timer-expired = Timer afgelopen
+timer-expired-body = Je Launch Control-timer is afgelopen.
+cleaning-mode = Schoonmaakmodus
+cleaning-description = Negeert toetsaanslagen een tijdje zodat het toetsenbord kan worden afgenomen. Gebruik de muis om eerder te stoppen.
+cleaning-left = Toetsen geblokkeerd, nog { $seconds } s
+start = Starten
+stop = Stoppen
+child-lock = Kinderslot
+lock-profile = Verlichting tijdens vergrendeling
+pending-changes = Het toetsenbord is niet verbonden. Je laatste wijziging wordt toegepast zodra het weer verbonden is.
+staged-editing = Wijzigingen handmatig toepassen
+staged-changes = Wijzigingen nog niet toegepast
+discard = Verwerpen
+max-brightness = Maximale helderheid
+quiet-hours = Stille uren
+quiet-hours-active = Verlichting is uit tijdens stille uren
+override = Negeren
+guest-mode = Gastmodus, wijzigingen worden niet bewaard
+demo-mode = Demomodus, wisselt tussen alle modi
+playlists = Afspeellijsten
+no-playlists = Nog geen afspeellijsten. Kies een profiel en een duur en voeg het toe aan een nieuwe afspeellijst met een naam.
+playlist-name = Naam van de afspeellijst
+playlist-entry = { $profile } ({ $minutes } min)
+add-to-playlist = Toevoegen aan afspeellijst
+play = Afspelen
+speed-slow = Langzaam
+speed-medium = Gemiddeld
+speed-fast = Snel
+applied-differently = Het toetsenbord heeft niet alles toegepast zoals gevraagd: { $changes }
+history = Geschiedenis
+history-empty = De verlichting is niet veranderd sinds de applet gestart is.
+history-manual = Handmatig gewijzigd
+history-rule = Regel { $rule }
+history-child-lock = Kinderslot
+history-quiet-hours = Stille uren
+history-playlist = Afspeellijst { $name }
+history-playlist-stopped = Afspeellijst gestopt
+history-demo = Demomodus
+history-timer = Timer
+copy-history = Geschiedenis kopiëren
+simulate = Situatie simuleren
+simulate-time = Tijd
+simulate-time-now = Nu, of UU:MM
+simulate-run = Welke regel wint?
+yielded-to = { $name } gebruikt het toetsenbord. Wijzigingen worden toegepast zodra het afgesloten is.
+external-controller = { $names } past mogelijk ook de toetsenbordverlichting aan.
+yield-to = Toetsenbord overlaten aan { $name }
+advanced = Geavanceerd…
+cancel = Annuleren
+delete = Verwijderen
+save = Opslaan
+confirm-delete-profile = Het profiel “{ $name }” verwijderen? Regels die het gebruiken worden ook verwijderd.
+link-health = Verbinding met het toetsenbord
+link-healthy = In orde
+link-slow = Het toetsenbord reageert traag, waardoor wijzigingen vertraagd kunnen zijn.
+link-degraded = { $errors ->
+    [one] Een opdracht aan het toetsenbord is onlangs mislukt; wijzigingen kunnen vertraagd zijn of niet worden toegepast.
+   *[other] { $errors } opdrachten aan het toetsenbord zijn onlangs mislukt; wijzigingen kunnen vertraagd zijn of niet worden toegepast.
+}
+save-to-keyboard = Op toetsenbord opslaan
+confirm-save-to-keyboard = De huidige verlichting op het toetsenbord opslaan? Die wordt gebruikt na loskoppelen en op andere computers.
+brightness-cap = Helderheidslimiet
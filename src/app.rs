@@ -1,60 +1,2553 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use cosmic::applet::cosmic_panel_config::{PanelSize, PanelAnchor};
-use cosmic::applet::{PanelType, Size};
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use chrono::Timelike;
 use cosmic::app::{Core, Task};
-use cosmic::iced::futures::channel;
-use cosmic::iced::{Limits, Subscription, window::Id,};
-use cosmic::iced::Limits;
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::widget::scrollable::{self, RelativeOffset};
+use cosmic::iced::{
+    stream, time,
+    window::{self, Id},
+    Alignment, Length, Limits, Size, Subscription,
+};
 use cosmic::iced_winit::commands::popup::{destroy_popup, get_popup};
-use cosmic::widget::{self, settings, vertical_space, slider, list_column};
+use cosmic::widget::{self, list_column, segmented_button, settings};
+
+use cosmic::{Application, Element};
+
+use strum::IntoEnumIterator;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::bus::{Event, EventBus};
+use crate::config::{
+    Arrangement, BrightnessCap, Config, ModeOrder, NotificationsConfig, PopupWidth, QuietHours,
+    Section, SliderStep, Tab, YieldTo, NOTIFICATIONS_ID,
+};
+use crate::conflicts::{self, ExternalController};
+use crate::device_listener::DeviceListener;
+use crate::diagnostics::{LatencyReport, Metrics, StressReport};
+use crate::fl;
+use crate::format;
+use crate::indicators::{self, IndicatorSource};
+use crate::kmsg;
+use crate::launch::{Change, Color, Launch, LedMode, LinkHealth, Snapshot};
+use crate::notify;
+use crate::playlist::{Playback, Playlist, PlaylistEntry};
+use crate::power;
+use crate::presets::{BundledPresets, PresetSource};
+use crate::privacy::DeviceUsage;
+use crate::profile::Profile;
+use crate::rules::{self, Context, Days, Rule, TriggerKind};
+use crate::scene::Scene;
+use crate::search;
+use crate::session;
+use crate::shutdown::{self, ExitReason};
+use crate::supervisor;
+use crate::widgets::{self, Throttle};
+
+/// Id of the mode search field, focused whenever the popup opens.
+static SEARCH_ID: LazyLock<widget::Id> = LazyLock::new(|| widget::Id::new("mode-search"));
+
+/// Id of the scrollable holding the active tab's content.
+static PAGE_SCROLL_ID: LazyLock<widget::Id> = LazyLock::new(|| widget::Id::new("page-scroll"));
+
+/// Labels for the trigger dropdown, in [`TriggerKind::all`] order.
+static TRIGGER_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    TriggerKind::all()
+        .into_iter()
+        .map(TriggerKind::label)
+        .collect()
+});
+
+/// Labels for the days dropdown, in [`Days::all`] order.
+static DAYS_LABELS: LazyLock<Vec<String>> =
+    LazyLock::new(|| Days::all().into_iter().map(Days::label).collect());
+
+/// Number of EC round trips in a latency measurement.
+const LATENCY_SAMPLES: usize = 200;
+/// Round trips measured per update, so the popup stays responsive.
+const LATENCY_STEP: usize = 10;
+
+/// Labels for the slider step dropdown, in [`SliderStep::CHOICES`] order.
+static STEP_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    SliderStep::CHOICES
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+});
+
+/// Labels for the popup width dropdown, in [`PopupWidth::CHOICES`] order.
+static WIDTH_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    PopupWidth::CHOICES
+        .iter()
+        .map(|width| fl!("pixels", pixels = *width))
+        .collect()
+});
+
+/// Labels for the brightness cap dropdown, in [`BrightnessCap::CHOICES`]
+/// order.
+static CAP_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    BrightnessCap::CHOICES
+        .iter()
+        .map(|percent| format!("{percent}%"))
+        .collect()
+});
+
+/// Cleaning mode lengths offered in the device tab, in seconds.
+const CLEANING_SECONDS: [i64; 3] = [30, 60, 120];
+
+/// Labels for the cleaning mode length dropdown, in [`CLEANING_SECONDS`] order.
+static CLEANING_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    CLEANING_SECONDS
+        .iter()
+        .map(|seconds| fl!("seconds", seconds = *seconds))
+        .collect()
+});
+
+/// Timer lengths offered in the popup, in minutes.
+const TIMER_MINUTES: [i64; 4] = [5, 10, 25, 50];
+/// Times the keyboard blinks when a timer goes off.
+const FLASH_PULSES: u8 = 3;
+/// How long each half of a blink lasts.
+const FLASH_STEP: Duration = Duration::from_millis(400);
+
+/// Entry lengths offered in the playlist editor, in minutes.
+const PLAYLIST_MINUTES: [u32; 4] = [1, 5, 15, 60];
+
+/// Labels for the playlist entry length dropdown, in [`PLAYLIST_MINUTES`]
+/// order.
+static PLAYLIST_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    PLAYLIST_MINUTES
+        .iter()
+        .map(|minutes| fl!("timer-minutes", minutes = *minutes))
+        .collect()
+});
+
+/// How long demo mode shows each mode.
+const DEMO_STEP: Duration = Duration::from_secs(10);
+
+/// How often to check whether another program controlling the keyboard was
+/// started or quit.
+const CONFLICT_CHECK: Duration = Duration::from_secs(5);
+
+/// How often the keyboard is read back while a popup or window shows its
+/// state, to catch changes made by other programs.
+const STATE_REFRESH: Duration = Duration::from_secs(3);
+
+/// Modes listed in the quick popup while nothing is searched.
+const FAVORITES: usize = 6;
+
+/// Entries kept in the diagnostics event log.
+const EVENT_LOG_LEN: usize = 50;
+/// Entries kept in the lighting history.
+const HISTORY_LEN: usize = 100;
+
+/// Stress test lengths offered in the diagnostics tab, in seconds.
+const STRESS_SECONDS: [u64; 3] = [10, 30, 60];
+
+/// Labels for the stress test length dropdown, in [`STRESS_SECONDS`] order.
+static STRESS_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    STRESS_SECONDS
+        .iter()
+        .map(|seconds| fl!("seconds", seconds = *seconds))
+        .collect()
+});
+
+/// What a popup or window of the applet shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Surface {
+    /// The quick controls opened from the panel button.
+    Popup,
+    /// The standalone window with the full tabbed editor.
+    Editor,
+    /// A small window asking before a destructive action.
+    Confirm(Confirmation),
+}
+
+/// An action that only happens once the user confirms it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Deleting a profile, which also deletes the rules using it.
+    DeleteProfile { name: String },
+    /// Writing the current lighting to the keyboard's flash.
+    SaveToKeyboard,
+}
+
+impl Confirmation {
+    fn question(&self) -> String {
+        match self {
+            Self::DeleteProfile { name } => {
+                fl!("confirm-delete-profile", name = name.as_str())
+            }
+            Self::SaveToKeyboard => fl!("confirm-save-to-keyboard"),
+        }
+    }
+
+    fn action_label(&self) -> String {
+        match self {
+            Self::DeleteProfile { .. } => fl!("delete"),
+            Self::SaveToKeyboard => fl!("save"),
+        }
+    }
+
+    /// The message carrying out the action.
+    fn message(&self) -> Message {
+        match self {
+            Self::DeleteProfile { name } => Message::DeleteProfile(name.clone()),
+            Self::SaveToKeyboard => Message::SaveToKeyboard,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LaunchControl {
+    /// Application state which is managed by the COSMIC runtime.
+    core: Core,
+    /// Open popups and windows, so each renders its own content.
+    surfaces: HashMap<Id, Surface>,
+    /// Tabs shown at the top of the popup.
+    tabs: segmented_button::SingleSelectModel,
+    /// Where each tab was last scrolled to, restored when it's shown again.
+    scroll_offsets: HashMap<Tab, RelativeOffset>,
+    /// Persistent applet configuration.
+    config: Config,
+    /// Handle used to write configuration changes back to disk.
+    config_handler: Option<cosmic_config::Config>,
+    /// Handle to COSMIC's notification settings, for toggling Do Not Disturb.
+    notifications_handler: Option<cosmic_config::Config>,
+    /// Whether COSMIC's Do Not Disturb mode is on.
+    dnd: bool,
+    /// Channel shared with the background subsystems.
+    bus: EventBus,
+    /// Names of the saved profiles, for the rule editor's dropdown.
+    profile_names: Vec<String>,
+    /// "Leave as is" followed by the profile names, for the exit state
+    /// dropdown.
+    exit_options: Vec<String>,
+    /// The profile names followed by "Live keyboard", for the comparison
+    /// dropdowns.
+    compare_options: Vec<String>,
+    /// Indices into `compare_options` of the two sides being compared.
+    compare: (Option<usize>, Option<usize>),
+    /// The connected keyboard, if any.
+    launch: Option<Launch>,
+    /// Keyboard state when it was first seen this session.
+    login_snapshot: Option<Snapshot>,
+    /// Keyboard state as of the last refresh.
+    snapshot: Option<Snapshot>,
+    /// Text typed into the mode search field.
+    search: String,
+    /// Text typed into the profile search field.
+    profile_search: String,
+    /// Kernel port path of the keyboard, kept after it disconnects so the
+    /// kernel's messages about it can still be matched.
+    usb_port: Option<String>,
+    /// Recent device events and failures with their Unix timestamps, oldest
+    /// first.
+    event_log: VecDeque<(i64, String)>,
+    /// Lighting changes with their Unix timestamps, oldest first. Each entry
+    /// says what made the change and what changed.
+    history: VecDeque<(i64, String)>,
+    /// Lighting after the latest change in the history.
+    history_state: Option<Profile>,
+    /// Last background task failure, shown until dismissed.
+    task_failure: Option<String>,
+    /// Counters shown in the diagnostics tab.
+    metrics: Metrics,
+    /// Round trip times collected by a running latency measurement.
+    latency_run: Option<Vec<Duration>>,
+    /// Result of the last latency measurement.
+    latency: Option<LatencyReport>,
+    /// Index into [`STRESS_SECONDS`] of the selected stress test length.
+    stress_length: usize,
+    /// Running tally of a stress test in progress. The test has the keyboard
+    /// to itself meanwhile, so `launch` stays closed.
+    stress_run: Option<StressReport>,
+    /// Result of the last stress test.
+    stress: Option<StressReport>,
+    /// Speed shown while the speed slider is being dragged.
+    speed_preview: Option<u8>,
+    /// Brightness shown while the brightness slider is being dragged.
+    brightness_preview: Option<u8>,
+    /// Text typed into the brightness field, until it's submitted.
+    brightness_input: Option<String>,
+    /// Color shown while one of the color sliders is being dragged.
+    color_preview: Option<Color>,
+    /// Index into [`CLEANING_SECONDS`] of the selected cleaning mode length.
+    cleaning_length: usize,
+    /// Other programs controlling the keyboard that are running.
+    external: Vec<ExternalController>,
+    /// The running program the keyboard was left to. The handle stays closed
+    /// until it quits.
+    yielded: Option<ExternalController>,
+    /// Lighting requested while the keyboard was disconnected, applied once
+    /// it's back. Only the latest request is kept.
+    pending: Option<Profile>,
+    /// Lighting put together in staged editing, written once it's applied.
+    staged: Option<Profile>,
+    /// Lighting from before the child lock was turned on, restored when it's
+    /// turned off again.
+    lock_restore: Option<Profile>,
+    /// Lighting from before quiet hours began, restored when they end. Set
+    /// while quiet hours are in effect, and updated by changes made meanwhile.
+    quiet_restore: Option<Profile>,
+    /// Whether quiet hours were lifted until the current window ends.
+    quiet_override: bool,
+    /// Set while guest mode is on, in which nothing is saved.
+    guest: Option<Guest>,
+    /// Lighting from before demo mode, put back when it stops. Set while
+    /// demo mode cycles through the modes.
+    demo_restore: Option<Profile>,
+    /// Number of modes demo mode has shown so far.
+    demo_index: usize,
+    /// The playlist being played, if any.
+    playback: Option<Playback>,
+    /// Name typed in the playlist editor.
+    playlist_name: String,
+    /// Index into `profile_names` of the profile to add to a playlist.
+    playlist_profile: Option<usize>,
+    /// Index into [`PLAYLIST_MINUTES`] of the selected entry length.
+    playlist_minutes: usize,
+    /// Quiet hours being typed in the device tab.
+    quiet_input: Option<String>,
+    /// Unix timestamp at which cleaning mode ends, while key input is
+    /// blocked.
+    cleaning_until: Option<i64>,
+    /// Unix timestamps at which the running timers go off, soonest first.
+    timers: Vec<i64>,
+    /// Lighting to restore once the timer flash finishes, while it runs.
+    flash_restore: Option<(LedMode, u8)>,
+    /// Whether the modes tab shows its arrangement editor.
+    arranging: bool,
+    /// Text typed into the speed field, until it's submitted.
+    speed_input: Option<String>,
+    /// Limits how often speed previews are written to the keyboard.
+    preview_throttle: Throttle,
+    /// Example profiles offered in the presets section.
+    presets: Vec<Profile>,
+    /// Name for the next scene saved from the current rules.
+    scene_name: String,
+    /// Index into `profile_names` of the profile the next scene applies.
+    scene_profile: Option<usize>,
+    /// Name of the last activated scene.
+    active_scene: Option<String>,
+    /// Name for the next profile saved from the current lighting.
+    profile_name: String,
+    /// The rule being composed in the rule editor.
+    rule_draft: RuleDraft,
+    /// Latest readings of the indicator sources rules depend on.
+    indicators: HashMap<IndicatorSource, u32>,
+    /// Latest microphone and camera usage, read while rules need it.
+    device_usage: DeviceUsage,
+    /// Whether the session was locked when last checked for rules.
+    locked: bool,
+    /// The context the rules were last evaluated in.
+    context: Context,
+    /// The rule whose profile was applied last, if the lighting came from one.
+    active_rule: Option<Rule>,
+    /// Lighting from before the rules took over, put back once none holds.
+    rule_restore: Option<Profile>,
+    /// Dry-run explanation of the current rule outcome, shown on request.
+    explanation: Option<String>,
+    /// Hypothetical situation the rules are simulated against.
+    simulation: Context,
+    /// Time of day typed for the simulation, `HH:MM`. Empty means now.
+    simulation_time: String,
+}
+
+/// What guest mode puts back when it ends.
+struct Guest {
+    /// The config from before guest mode. Changes made meanwhile only touch
+    /// the copy in [`LaunchControl::config`].
+    config: Config,
+    /// Lighting from before guest mode.
+    lighting: Option<Profile>,
+}
+
+#[derive(Default)]
+struct RuleDraft {
+    kind: usize,
+    param: String,
+    days: usize,
+    profile: Option<usize>,
+}
+
+impl RuleDraft {
+    fn days(&self) -> Days {
+        Days::all()[self.days]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    TogglePopup,
+    PopupClosed(Id),
+    OpenWindow,
+    Confirm(Confirmation),
+    /// The answer given in a confirmation window.
+    Answer(Id, bool),
+    SaveToKeyboard,
+    TabActivated(segmented_button::Entity),
+    PageScrolled(RelativeOffset),
+    UpdateConfig(Config),
+    Bus(Event),
+    SearchChanged(String),
+    ProfileSearchChanged(String),
+    SearchSubmitted,
+    SetMode(LedMode),
+    SortByUsage(bool),
+    QuietHoursInput(String),
+    QuietHoursSubmit,
+    OverrideQuietHours,
+    GuestMode(bool),
+    DemoMode(bool),
+    DemoStep,
+    PlaylistNameChanged(String),
+    PlaylistProfileSelected(usize),
+    PlaylistMinutesSelected(usize),
+    AddToPlaylist,
+    PlayPlaylist(usize),
+    StopPlaylist,
+    DeletePlaylist(usize),
+    StagedEditing(bool),
+    ApplyStaged,
+    DiscardStaged,
+    ProfileNameChanged(String),
+    SaveProfile,
+    ApplyProfile(usize),
+    /// Deletes the named profile. Names stay valid while the list changes
+    /// under an open confirmation, indices don't.
+    DeleteProfile(String),
+    ApplyPreset(usize),
+    SceneNameChanged(String),
+    SceneProfileSelected(usize),
+    SaveScene,
+    ActivateScene(usize),
+    DeleteScene(usize),
+    AddPreset(usize),
+    RuleKindSelected(usize),
+    RuleParamChanged(String),
+    RuleProfileSelected(usize),
+    AddRule,
+    ToggleRule(usize, bool),
+    MoveRule(usize, i32),
+    DeleteRule(usize),
+    RuleDaysSelected(usize),
+    SkipRule(usize),
+    ResumeRule(usize),
+    EvaluateRules,
+    RuleInputsRead(HashMap<IndicatorSource, u32>, DeviceUsage, bool),
+    ExplainRules,
+    CheckConflicts,
+    ConflictsChecked(Vec<ExternalController>),
+    PollState,
+    ExternalControllerDetected(ExternalController),
+    YieldTo(ExternalController, bool),
+    SimulatedApp(String),
+    SimulatedTime(String),
+    SimulatedWeekend(bool),
+    SimulatedBattery(bool),
+    SimulatedLocked(bool),
+    SimulatedDnd(bool),
+    Simulate,
+    RefreshSnapshot,
+    CopyColor(String),
+    CopyMetrics(String),
+    CopyHistory(String),
+    DismissTaskFailure,
+    ExitProfileSelected(usize),
+    CompareFirst(usize),
+    ChildLock(bool),
+    LockProfileSelected(usize),
+    CleaningLengthSelected(usize),
+    StartCleaning,
+    StopCleaning,
+    StartTimer(i64),
+    CancelTimer(usize),
+    TimerNotification(bool),
+    TimerTick,
+    FlashStep(u8),
+    ToggleArranging,
+    ShowSection(Section, bool),
+    MoveSection(Section, i32),
+    PopupWidthSelected(usize),
+    BrightnessCapSelected(usize),
+    CompareSecond(usize),
+    LogoutProfileSelected(usize),
+    DndChanged(bool),
+    ToggleDnd(bool),
+    MeasureLatency,
+    LatencyStep,
+    StressLengthSelected(usize),
+    StartStress,
+    StressProgress(StressReport),
+    StressFinished(Result<StressReport, String>),
+    SpeedPreview(u8),
+    SpeedCommit,
+    SpeedInput(String),
+    SetSpeed(u8),
+    BrightnessPreview(u8),
+    BrightnessCommit,
+    BrightnessInput(String),
+    SetBrightness(u8),
+    ColorPreview(Color),
+    ColorCommit,
+    SliderStepSelected(usize),
+}
+
+impl LaunchControl {
+    /// Writes a config change through one of the generated setters, logging
+    /// failures. In guest mode only the in-memory `field` changes.
+    fn write_config<T>(
+        &mut self,
+        value: T,
+        field: impl FnOnce(&mut Config) -> &mut T,
+        set: impl FnOnce(&mut Config, &cosmic_config::Config, T) -> Result<bool, cosmic_config::Error>,
+    ) {
+        if self.guest.is_some() {
+            *field(&mut self.config) = value;
+            return;
+        }
+        if let Some(handler) = self.config_handler.as_ref() {
+            if let Err(err) = set(&mut self.config, handler, value) {
+                eprintln!("failed to save config: {err}");
+            }
+        }
+    }
+
+    fn set_config(&mut self, config: Config) {
+        self.config = config;
+        self.sync_profile_names();
+        self.sync_brightness_limit();
+    }
+
+    /// Hands the configured brightness cap to the keyboard handle and lowers
+    /// the brightness if it's above the cap.
+    fn sync_brightness_limit(&mut self) {
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+        let limit = self.config.brightness_cap.limit(launch.max_brightness());
+        launch.set_brightness_limit(limit);
+        if launch.brightness() > limit {
+            if let Err(err) = launch.set_brightness(limit) {
+                self.log_event(format!("failed to apply the brightness cap: {err}"));
+            }
+        }
+    }
+
+    fn sync_profile_names(&mut self) {
+        self.profile_names = self
+            .config
+            .profiles
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        self.exit_options = std::iter::once(fl!("exit-keep"))
+            .chain(self.profile_names.iter().cloned())
+            .collect();
+        self.compare_options = self
+            .profile_names
+            .iter()
+            .cloned()
+            .chain(std::iter::once(fl!("live-keyboard")))
+            .collect();
+    }
+
+    /// Saves `profile`, replacing any saved profile with the same name.
+    fn save_profile(&mut self, profile: Profile) {
+        let mut profiles = self.config.profiles.clone();
+        match profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => {
+                // The lighting is replaced, effect settings it didn't capture stay.
+                let effects = std::mem::take(&mut existing.effects);
+                *existing = profile;
+                for (effect, config) in effects {
+                    existing.effects.entry(effect).or_insert(config);
+                }
+            }
+            None => profiles.push(profile),
+        }
+        self.write_config(
+            profiles,
+            |config| &mut config.profiles,
+            Config::set_profiles,
+        );
+        self.sync_profile_names();
+    }
+
+    /// Logs `entry` and keeps it for the diagnostics tab, so failures can be
+    /// lined up with what the kernel reported about the device.
+    fn log_event(&mut self, entry: String) {
+        eprintln!("{entry}");
+        if self.event_log.len() == EVENT_LOG_LEN {
+            self.event_log.pop_front();
+        }
+        self.event_log
+            .push_back((chrono::Local::now().timestamp(), entry));
+    }
+
+    /// Opens the keyboard, closing the current handle first.
+    fn open_launch(&mut self) {
+        let was_open = match self.launch.take() {
+            Some(launch) => {
+                self.metrics.retire(launch.stats());
+                true
+            }
+            None => false,
+        };
+        if self.yielded.is_some() || self.stress_run.is_some() {
+            return;
+        }
+        self.launch = Launch::try_new().ok();
+        if self.launch.is_some() && !was_open {
+            self.metrics.opened();
+        }
+        if let Some(usb) = self.launch.as_ref().and_then(Launch::usb) {
+            self.usb_port = Some(usb.port_path.clone());
+        }
+        self.capture_device_default();
+        self.sync_brightness_limit();
+        // Plugging the keyboard back in clears its input block.
+        if self.config.child_lock {
+            self.enforce_child_lock();
+        }
+        // A replugged or reclaimed keyboard comes back with the firmware's
+        // lighting, which quiet hours keep off.
+        if self.quiet_restore.is_some() {
+            if let Some(launch) = self.launch.as_mut() {
+                if launch.current_mode() != LedMode::Disabled {
+                    match launch.set_led_mode(LedMode::Disabled, launch.current_speed()) {
+                        Ok(()) => self.record_history(fl!("history-quiet-hours")),
+                        Err(err) => self.log_event(format!("failed to keep quiet hours: {err}")),
+                    }
+                }
+            }
+        }
+    }
+
+    fn profile_named(&self, name: &str) -> Option<Profile> {
+        self.config
+            .profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+    }
+
+    /// Blocks key input and applies the lock profile.
+    fn enforce_child_lock(&mut self) {
+        self.set_input_blocked(true);
+        let profile = self
+            .config
+            .lock_profile
+            .as_deref()
+            .and_then(|name| self.profile_named(name));
+        if let Some(profile) = profile {
+            self.apply_profile(&profile);
+        }
+        self.record_history(fl!("history-child-lock"));
+    }
+
+    fn set_child_lock(&mut self, locked: bool) {
+        if locked {
+            if let Some(launch) = self.launch.as_ref() {
+                self.lock_restore = Some(Profile::from_launch(String::new(), launch));
+            }
+            self.write_config(
+                true,
+                |config| &mut config.child_lock,
+                Config::set_child_lock,
+            );
+            self.enforce_child_lock();
+            return;
+        }
+
+        if self.launch.is_some() && !self.set_input_blocked(false) {
+            return;
+        }
+        self.write_config(
+            false,
+            |config| &mut config.child_lock,
+            Config::set_child_lock,
+        );
+        match self.lock_restore.take() {
+            Some(profile) => {
+                if self.defer_to_quiet_hours(&profile) {
+                    return;
+                }
+                if let Some(launch) = self.launch.as_mut() {
+                    match launch.apply(&profile) {
+                        Ok(()) => {
+                            self.record_history(fl!("history-child-lock"));
+                            self.write_journal();
+                        }
+                        Err(err) => self.log_event(format!("failed to restore lighting: {err}")),
+                    }
+                }
+            }
+            None => {
+                self.active_rule = None;
+                self.apply_rules();
+            }
+        }
+    }
+
+    /// Saves the lighting of a board seen for the first time as a profile,
+    /// so what it shipped with can always be restored.
+    fn capture_device_default(&mut self) {
+        let Some(launch) = self.launch.as_ref() else {
+            return;
+        };
+        let board = launch.board().clone();
+        if self.config.known_boards.contains(&board) {
+            return;
+        }
+        let name = fl!("device-default-profile", board = board.as_str());
+        let profile = Profile::from_launch(name, launch);
+        self.save_profile(profile);
+
+        let mut known_boards = self.config.known_boards.clone();
+        known_boards.push(board);
+        self.write_config(
+            known_boards,
+            |config| &mut config.known_boards,
+            Config::set_known_boards,
+        );
+    }
+
+    /// Keeps lighting requested during quiet hours for when they end.
+    /// Returns whether quiet hours took it, in which case nothing may be
+    /// written to the keyboard.
+    fn defer_to_quiet_hours(&mut self, profile: &Profile) -> bool {
+        match self.quiet_restore.as_mut() {
+            Some(restore) => {
+                restore.overlay(profile);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn apply_profile(&mut self, profile: &Profile) {
+        if self.defer_to_quiet_hours(profile) {
+            return;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            self.pending = Some(profile.clone());
+            return;
+        };
+        match profile.apply(launch) {
+            Ok(()) => self.bus.publish(Event::ProfileApplied {
+                profile: profile.name.clone(),
+                rule: None,
+            }),
+            Err(err) => self.log_event(format!("failed to apply profile {}: {err}", profile.name)),
+        }
+    }
+
+    /// Adds what changed since the previous entry to the history, credited
+    /// to `source`. Writers other than the popup call this right after
+    /// writing, so their changes aren't blamed on the next manual one.
+    fn record_history(&mut self, source: String) {
+        let Some(launch) = self.launch.as_ref() else {
+            return;
+        };
+        let state = Profile::from_launch(String::new(), launch);
+        let previous = self
+            .history_state
+            .replace(state.clone())
+            .or_else(|| self.config.journal.clone());
+        let changes = previous
+            .map(|previous| previous.diff(&state))
+            .unwrap_or_default();
+        if changes.is_empty() {
+            return;
+        }
+
+        let changes: Vec<String> = changes.iter().map(describe_change).collect();
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back((
+            chrono::Local::now().timestamp(),
+            format!("{source}: {}", changes.join(", ")),
+        ));
+    }
+
+    /// Records the keyboard's lighting as the state to come back to.
+    /// Temporary lighting from the child lock, quiet hours, demo mode or a
+    /// timer flash isn't.
+    fn write_journal(&mut self) {
+        let temporary = self.config.child_lock
+            || self.quiet_restore.is_some()
+            || self.demo_restore.is_some()
+            || self.flash_restore.is_some();
+        let Some(launch) = self.launch.as_ref().filter(|_| !temporary) else {
+            return;
+        };
+        let state = Profile::from_launch(String::new(), launch);
+        if self.config.journal.as_ref() != Some(&state) {
+            self.write_config(
+                Some(state),
+                |config| &mut config.journal,
+                Config::set_journal,
+            );
+        }
+    }
+
+    /// Puts back the journaled lighting if the keyboard lost it, e.g. because
+    /// it was power cycled while the applet wasn't running.
+    fn restore_journal(&mut self) {
+        let (Some(launch), Some(state)) = (self.launch.as_ref(), self.config.journal.clone())
+        else {
+            return;
+        };
+        let lost = !state
+            .diff(&Profile::from_launch(String::new(), launch))
+            .is_empty();
+        if lost && !self.config.child_lock {
+            eprintln!("restoring lighting from the journal");
+            self.apply_profile(&state);
+        }
+    }
+
+    /// Applies the lighting requested while the keyboard was away. It was
+    /// asked for last, so it wins over rules applied on reconnect.
+    fn apply_pending(&mut self) {
+        if self.launch.is_none() || self.config.child_lock {
+            return;
+        }
+        if let Some(pending) = self.pending.take() {
+            self.apply_profile(&pending);
+        }
+    }
+
+    fn set_rules(&mut self, mut rules: Vec<Rule>) {
+        rules.sort_by_key(|rule| Reverse(rule.priority));
+        self.write_config(rules, |config| &mut config.rules, Config::set_rules);
+        self.explanation = None;
+        self.apply_rules();
+    }
+
+    fn current_context(&self) -> Context {
+        Context {
+            do_not_disturb: self.dnd,
+            microphone_in_use: self.device_usage.microphone,
+            camera_in_use: self.device_usage.camera,
+            locked: self.locked,
+            indicators: self.indicators.clone(),
+            ..Context::current(self.launch.is_some())
+        }
+    }
+
+    /// Applies the profile of the winning rule, if it changed since the last
+    /// evaluation. Manual changes stick until a different rule wins; once no
+    /// rule holds, the lighting from before the rules took over comes back.
+    fn apply_rules(&mut self) {
+        self.context = self.current_context();
+        // The lock profile stays until the child lock is turned off.
+        if self.config.child_lock || self.enforce_quiet_hours() {
+            return;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+
+        let winner = rules::evaluate(&self.config.rules, &self.context)
+            .winner
+            .cloned();
+        if winner == self.active_rule {
+            return;
+        }
+
+        if let Some(rule) = winner.as_ref() {
+            let Some(profile) = self.config.profiles.iter().find(|p| p.name == rule.profile) else {
+                return;
+            };
+            if self.active_rule.is_none() && self.rule_restore.is_none() {
+                self.rule_restore = Some(Profile::from_launch(String::new(), launch));
+            }
+            if let Err(err) = profile.apply(launch) {
+                let entry = format!("failed to apply profile {}: {err}", profile.name);
+                self.log_event(entry);
+                return;
+            }
+            eprintln!(
+                "rule \"{}\" (priority {}) applied profile \"{}\"",
+                rule.trigger.describe(),
+                rule.priority,
+                profile.name
+            );
+            self.bus.publish(Event::ProfileApplied {
+                profile: profile.name.clone(),
+                rule: Some(rule.trigger.describe()),
+            });
+        }
+        let previous = std::mem::replace(&mut self.active_rule, winner);
+        if let Some(previous) = previous.filter(|_| self.active_rule.is_none()) {
+            self.restore_from_rules(fl!("history-rule", rule = previous.trigger.describe()));
+        }
+    }
+
+    /// Puts back the lighting from before the rules took over.
+    fn restore_from_rules(&mut self, source: String) {
+        let Some(profile) = self.rule_restore.take() else {
+            return;
+        };
+        if self.defer_to_quiet_hours(&profile) {
+            return;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+        match launch.apply(&profile) {
+            Ok(()) => {
+                self.record_history(source);
+                self.write_journal();
+            }
+            Err(err) => self.log_event(format!("failed to restore lighting: {err}")),
+        }
+    }
+
+    /// Turns the lighting off when quiet hours begin and restores it when
+    /// they end or are overridden. Returns whether they're in effect.
+    fn enforce_quiet_hours(&mut self) -> bool {
+        let inside = self
+            .config
+            .quiet_hours
+            .is_some_and(|quiet| quiet.contains(self.context.minutes));
+        if !inside {
+            self.quiet_override = false;
+        }
+
+        if inside && !self.quiet_override {
+            if self.quiet_restore.is_none() {
+                if let Some(launch) = self.launch.as_mut() {
+                    let restore = Profile::from_launch(String::new(), launch);
+                    match launch.set_led_mode(LedMode::Disabled, restore.speed) {
+                        Ok(()) => {
+                            self.quiet_restore = Some(restore);
+                            self.record_history(fl!("history-quiet-hours"));
+                        }
+                        Err(err) => self.log_event(format!("failed to start quiet hours: {err}")),
+                    }
+                }
+            }
+            return true;
+        }
+
+        if let Some(restore) = self.quiet_restore.take() {
+            if let Some(launch) = self.launch.as_mut() {
+                match launch.apply(&restore) {
+                    Ok(()) => {
+                        self.record_history(fl!("history-quiet-hours"));
+                        self.write_journal();
+                    }
+                    Err(err) => self.log_event(format!("failed to restore lighting: {err}")),
+                }
+            }
+        }
+        false
+    }
+
+    fn refresh_snapshot(&mut self) {
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+        match launch.snapshot() {
+            Ok(snapshot) => {
+                self.login_snapshot.get_or_insert_with(|| snapshot.clone());
+                self.snapshot = Some(snapshot);
+            }
+            Err(err) => self.log_event(format!("failed to read keyboard state: {err}")),
+        }
+    }
+
+    /// Checks whether another tool changed the keyboard behind our back and,
+    /// if so, reloads everything shown instead of rendering stale values.
+    fn sync_external_changes(&mut self) {
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+        match launch.sync() {
+            Ok(false) => {}
+            Ok(true) => {
+                self.log_event(String::from("keyboard was changed by another program"));
+                self.refresh_snapshot();
+            }
+            Err(err) => self.log_event(format!("failed to read keyboard state: {err}")),
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::DeviceConnected(info) => {
+                if info.is_launch() && self.launch.is_none() {
+                    self.open_launch();
+                    if self.launch.is_some() {
+                        self.log_event(String::from("keyboard connected"));
+                    }
+                    self.refresh_snapshot();
+                    // A freshly connected keyboard has none of our rules applied.
+                    self.active_rule = None;
+                    self.apply_rules();
+                    self.apply_pending();
+                }
+            }
+            Event::DeviceDisconnected(info) => {
+                // Other devices coming and going don't concern the keyboard.
+                let ours = self
+                    .launch
+                    .as_ref()
+                    .map_or(true, |launch| info.pid == u32::from(launch.product_id()));
+                if !info.is_launch() || !ours {
+                    return;
+                }
+                // The model doesn't tell two keyboards of the same kind apart,
+                // so drop the handle and reopen it if the keyboard is still
+                // around.
+                let was_open = self.launch.is_some();
+                self.open_launch();
+                if was_open && self.launch.is_none() {
+                    self.log_event(String::from("keyboard disconnected"));
+                }
+                self.apply_pending();
+            }
+            Event::ShutdownRequested(reason) => {
+                // The child lock is persisted and blocks input again on the
+                // next start; until then the greeter needs a working keyboard.
+                let leaving = matches!(reason, ExitReason::Logout | ExitReason::PowerOff);
+                if self.cleaning_until.is_some() || (leaving && self.config.child_lock) {
+                    self.set_input_blocked(false);
+                }
+                let profile = match reason {
+                    ExitReason::Restart => self.config.exit_profile.as_ref(),
+                    ExitReason::Logout | ExitReason::PowerOff => {
+                        self.config.logout_profile.as_ref()
+                    }
+                };
+                let profile = profile
+                    .and_then(|name| self.config.profiles.iter().find(|p| &p.name == name))
+                    .cloned();
+                if let Some(profile) = profile {
+                    self.apply_profile(&profile);
+                }
+                std::process::exit(0);
+            }
+            Event::TaskRestarted { task, error } => {
+                self.log_event(format!("{task} {error}"));
+                self.task_failure = Some(fl!("task-restarted", task = task));
+            }
+            Event::KernelMessage(message) => {
+                let about_keyboard = self
+                    .usb_port
+                    .as_ref()
+                    .is_some_and(|port| message.starts_with(&format!("usb {port}:")));
+                if about_keyboard {
+                    self.log_event(format!("kernel: {message}"));
+                }
+            }
+            Event::ModeChanged(_)
+            | Event::SpeedChanged(_)
+            | Event::BrightnessChanged(_)
+            | Event::ColorChanged(_) => {
+                self.record_history(fl!("history-manual"));
+                self.write_journal();
+            }
+            Event::ProfileApplied { rule, .. } => {
+                let source = match rule {
+                    Some(rule) => fl!("history-rule", rule = rule),
+                    None => fl!("history-manual"),
+                };
+                self.record_history(source);
+                self.write_journal();
+            }
+            _ => {}
+        }
+    }
+
+    /// Modes matching the current search, best matches first.
+    fn filtered_modes(&self) -> Vec<LedMode> {
+        let mut modes: Vec<LedMode> = LedMode::iter()
+            .filter(|mode| *mode != LedMode::Last)
+            .collect();
+        if self.config.mode_order == ModeOrder::MostUsed {
+            // Stable sort, so modes with equal counts keep the default order.
+            modes.sort_by_key(|mode| Reverse(self.config.usage(*mode)));
+        }
+        search::filter(&self.search, modes, LedMode::to_string)
+    }
+
+    fn set_mode(&mut self, mode: LedMode) {
+        self.sync_external_changes();
+        if let Some(restore) = self.quiet_restore.as_mut() {
+            restore.mode = mode;
+            return;
+        }
+        // A running timer flash ends by writing this back.
+        if let Some(restore) = self.flash_restore.as_mut() {
+            restore.0 = mode;
+            return;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            let speed = self
+                .pending
+                .as_ref()
+                .map(|pending| pending.speed)
+                .or(self.snapshot.as_ref().map(|snapshot| snapshot.speed))
+                .unwrap_or_default();
+            self.pending = Some(Profile::new(String::new(), mode, speed));
+            return;
+        };
+        if self.stage(|staged| staged.mode = mode) {
+            return;
+        }
+        if let Err(err) = launch.set_led_mode(mode, launch.current_speed()) {
+            self.log_event(format!("failed to set led mode: {err}"));
+            return;
+        }
+        self.bus.publish(Event::ModeChanged(mode));
+
+        let mut usage = self.config.mode_usage.clone();
+        *usage.entry(mode).or_default() += 1;
+        self.write_config(
+            usage,
+            |config| &mut config.mode_usage,
+            Config::set_mode_usage,
+        );
+    }
+
+    /// Adds a change to the staged lighting instead of writing it. Returns
+    /// `false` when editing is live and the change should be written.
+    fn stage(&mut self, change: impl FnOnce(&mut Profile)) -> bool {
+        if !self.config.staged_editing {
+            return false;
+        }
+        let Some(launch) = self.launch.as_ref() else {
+            return false;
+        };
+        change(
+            self.staged
+                .get_or_insert_with(|| Profile::from_launch(String::new(), launch)),
+        );
+        true
+    }
+
+    fn apply_staged(&mut self) {
+        if let Some(staged) = self.staged.take() {
+            self.apply_profile(&staged);
+        }
+    }
+
+    /// Writes `speed` while keeping the current mode.
+    fn write_speed(&mut self, speed: u8) -> bool {
+        if let Some(restore) = self.quiet_restore.as_mut() {
+            restore.speed = speed;
+            return false;
+        }
+        if let Some(restore) = self.flash_restore.as_mut() {
+            restore.1 = speed;
+            return false;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return false;
+        };
+        match launch.set_led_mode(launch.current_mode(), speed) {
+            Ok(()) => true,
+            Err(err) => {
+                self.log_event(format!("failed to set led speed: {err}"));
+                false
+            }
+        }
+    }
+
+    fn write_brightness(&mut self, brightness: u8) -> bool {
+        if let Some(restore) = self.quiet_restore.as_mut() {
+            restore.brightness = Some(brightness);
+            return false;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return false;
+        };
+        match launch.set_brightness(brightness) {
+            Ok(()) => true,
+            Err(err) => {
+                self.log_event(format!("failed to set led brightness: {err}"));
+                false
+            }
+        }
+    }
+
+    fn write_color(&mut self, color: Color) -> bool {
+        if let Some(restore) = self.quiet_restore.as_mut() {
+            restore.color = Some(color);
+            return false;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return false;
+        };
+        match launch.set_color(color) {
+            Ok(()) => true,
+            Err(err) => {
+                self.log_event(format!("failed to set led color: {err}"));
+                false
+            }
+        }
+    }
+
+    fn active_tab(&self) -> Tab {
+        self.tabs.active_data::<Tab>().copied().unwrap_or_default()
+    }
+
+    /// Wraps a tab's content in the scrollable whose position is remembered.
+    fn page_scrollable<'a>(
+        &self,
+        content: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        widget::scrollable(content)
+            .id(PAGE_SCROLL_ID.clone())
+            .on_scroll(|viewport| Message::PageScrolled(viewport.relative_offset()))
+            .into()
+    }
+
+    /// Scrolls the active tab back to where it was left.
+    fn restore_scroll<T>(&self) -> cosmic::iced::Task<T> {
+        let offset = self
+            .scroll_offsets
+            .get(&self.active_tab())
+            .copied()
+            .unwrap_or(RelativeOffset::START);
+        scrollable::snap_to(PAGE_SCROLL_ID.clone(), offset)
+    }
+
+    fn mode_list(&self) -> Element<'_, Message> {
+        self.page_scrollable(self.mode_buttons(self.filtered_modes()))
+    }
+
+    fn mode_buttons(&self, modes: Vec<LedMode>) -> Element<'_, Message> {
+        let current = match (self.staged.as_ref(), self.launch.as_ref()) {
+            (Some(staged), _) => Some(staged.mode),
+            (None, Some(launch)) => Some(launch.current_mode()),
+            (None, None) => self.pending.as_ref().map(|pending| pending.mode),
+        };
+
+        if modes.is_empty() {
+            return widget::text::body(fl!("no-matching-modes")).into();
+        }
+
+        let mut list = list_column();
+        for mode in modes {
+            let mut row = widget::row()
+                .align_y(Alignment::Center)
+                .push(widget::text::body(mode.to_string()))
+                .push(widget::horizontal_space());
+            if Some(mode) == current {
+                row = row.push(widget::icon::from_name("object-select-symbolic").size(16));
+            }
+            list = list.add(
+                cosmic::applet::menu_button(row)
+                    .width(Length::Fill)
+                    .on_press(Message::SetMode(mode)),
+            );
+        }
+
+        list.into()
+    }
+
+    /// The id of the open surface of the given kind, if any.
+    fn surface(&self, kind: Surface) -> Option<Id> {
+        self.surfaces
+            .iter()
+            .find(|(_, surface)| **surface == kind)
+            .map(|(id, _)| *id)
+    }
+
+    /// Warnings and status lines shown above every surface's content, or
+    /// `None` when there are none.
+    fn notices(&self) -> Option<Element<'_, Message>> {
+        let banner = self
+            .task_failure
+            .as_deref()
+            .map(|failure| widget::warning(failure).on_close(Message::DismissTaskFailure));
+        let pending = if let Some(controller) = self.yielded {
+            Some(widget::text::caption(fl!(
+                "yielded-to",
+                name = controller.name()
+            )))
+        } else {
+            self.pending
+                .as_ref()
+                .map(|_| widget::text::caption(fl!("pending-changes")))
+        };
+        let mismatch = self
+            .launch
+            .as_ref()
+            .filter(|launch| !launch.mismatches().is_empty())
+            .map(|launch| {
+                let changes: Vec<String> =
+                    launch.mismatches().iter().map(describe_change).collect();
+                widget::warning(fl!("applied-differently", changes = changes.join(", ")))
+            });
+        let external = (self.yielded.is_none() && !self.external.is_empty()).then(|| {
+            let names: Vec<&str> = self.external.iter().map(|c| c.name()).collect();
+            widget::warning(fl!("external-controller", names = names.join(", ")))
+        });
+        let health = self
+            .launch
+            .as_ref()
+            .map(|launch| launch.health())
+            .filter(|health| *health != LinkHealth::Healthy)
+            .map(|health| widget::text::caption(describe_health(health)));
+
+        if banner.is_none()
+            && pending.is_none()
+            && mismatch.is_none()
+            && external.is_none()
+            && health.is_none()
+        {
+            return None;
+        }
+        let notices = widget::column()
+            .spacing(8)
+            .push_maybe(banner)
+            .push_maybe(pending)
+            .push_maybe(mismatch)
+            .push_maybe(external)
+            .push_maybe(health);
+        Some(notices.into())
+    }
+
+    /// The small popup: search, the most used modes and the speed, with the
+    /// full editor a click away.
+    fn quick_view(&self) -> Element<'_, Message> {
+        let mut modes = self.filtered_modes();
+        if self.search.trim().is_empty() {
+            // Stable sort, so unused modes keep the default order.
+            modes.sort_by_key(|mode| Reverse(self.config.usage(*mode)));
+            modes.truncate(FAVORITES);
+        }
+
+        widget::column()
+            .spacing(8)
+            .push_maybe(self.section_view(Section::Search))
+            .push(self.mode_buttons(modes))
+            .push_maybe(self.section_view(Section::Speed))
+            .push_maybe(self.section_view(Section::Brightness))
+            .push_maybe(self.section_view(Section::Color))
+            .push(
+                widget::row()
+                    .spacing(8)
+                    .push(
+                        widget::button::standard(fl!("save-to-keyboard")).on_press_maybe(
+                            (self.launch.is_some() && self.guest.is_none())
+                                .then_some(Message::Confirm(Confirmation::SaveToKeyboard)),
+                        ),
+                    )
+                    .push(widget::button::standard(fl!("advanced")).on_press(Message::OpenWindow)),
+            )
+            .into()
+    }
+
+    fn section_view(&self, section: Section) -> Option<Element<'_, Message>> {
+        let element = match section {
+            Section::DoNotDisturb => settings::item(
+                fl!("do-not-disturb"),
+                widget::toggler(self.dnd).on_toggle(Message::ToggleDnd),
+            )
+            .into(),
+            Section::Search => widget::search_input(fl!("search-modes"), &self.search)
+                .id(SEARCH_ID.clone())
+                .on_input(Message::SearchChanged)
+                .on_submit(|_| Message::SearchSubmitted)
+                .into(),
+            Section::ModeOrder => settings::item(
+                fl!("most-used-first"),
+                widget::toggler(self.config.mode_order == ModeOrder::MostUsed)
+                    .on_toggle(Message::SortByUsage),
+            )
+            .into(),
+            Section::Speed => {
+                let launch = self.launch.as_ref()?;
+                let mode = self
+                    .staged
+                    .as_ref()
+                    .map_or(launch.current_mode(), |staged| staged.mode);
+                let info = mode.info();
+                if !info.uses_speed {
+                    return None;
+                }
+                let speed = self
+                    .speed_preview
+                    .or(self.staged.as_ref().map(|staged| staged.speed))
+                    .unwrap_or(launch.current_speed());
+                let labels = [fl!("speed-slow"), fl!("speed-medium"), fl!("speed-fast")];
+                let mut presets = widget::row().spacing(4);
+                for (label, preset) in labels.into_iter().zip(info.speed_presets) {
+                    let button = if preset == speed {
+                        widget::button::suggested(label)
+                    } else {
+                        widget::button::standard(label)
+                    };
+                    presets = presets.push(button.on_press(Message::SetSpeed(preset)));
+                }
+                let slider = settings::item(
+                    fl!("speed"),
+                    widgets::numeric_slider(
+                        0..=255,
+                        speed,
+                        self.config.slider_step.0,
+                        self.speed_input.as_deref(),
+                        Message::SpeedPreview,
+                        Message::SpeedCommit,
+                        Message::SpeedInput,
+                        Message::SetSpeed,
+                    ),
+                );
+                widget::column()
+                    .spacing(4)
+                    .push(presets)
+                    .push(slider)
+                    .into()
+            }
+            Section::Brightness => {
+                let launch = self.launch.as_ref()?;
+                settings::item(
+                    fl!("brightness"),
+                    widgets::numeric_slider(
+                        0..=launch.brightness_limit(),
+                        self.brightness_preview
+                            .or(self.staged.as_ref().and_then(|staged| staged.brightness))
+                            .unwrap_or(launch.brightness()),
+                        self.config.slider_step.0,
+                        self.brightness_input.as_deref(),
+                        Message::BrightnessPreview,
+                        Message::BrightnessCommit,
+                        Message::BrightnessInput,
+                        Message::SetBrightness,
+                    ),
+                )
+                .into()
+            }
+            Section::Color => {
+                let launch = self.launch.as_ref()?;
+                let mode = self
+                    .staged
+                    .as_ref()
+                    .map_or(launch.current_mode(), |staged| staged.mode);
+                if !mode.info().uses_color {
+                    return None;
+                }
+                let color = self
+                    .color_preview
+                    .or(self.staged.as_ref().and_then(|staged| staged.color))
+                    .unwrap_or(launch.color());
+                let hue = widgets::preview_slider(
+                    0..=255,
+                    color.hue,
+                    move |hue| Message::ColorPreview(Color { hue, ..color }),
+                    Message::ColorCommit,
+                );
+                let saturation = widgets::preview_slider(
+                    0..=255,
+                    color.saturation,
+                    move |saturation| {
+                        Message::ColorPreview(Color {
+                            saturation,
+                            ..color
+                        })
+                    },
+                    Message::ColorCommit,
+                );
+                list_column()
+                    .add(settings::item(
+                        fl!("color"),
+                        widget::text::body(color.to_hex()),
+                    ))
+                    .add(settings::item(fl!("hue"), hue))
+                    .add(settings::item(fl!("saturation"), saturation))
+                    .into()
+            }
+            Section::SliderStep => settings::item(
+                fl!("slider-step"),
+                widget::dropdown(
+                    STEP_LABELS.as_slice(),
+                    SliderStep::CHOICES
+                        .iter()
+                        .position(|step| *step == self.config.slider_step.0),
+                    Message::SliderStepSelected,
+                ),
+            )
+            .into(),
+            Section::Editing => settings::item(
+                fl!("staged-editing"),
+                widget::toggler(self.config.staged_editing).on_toggle(Message::StagedEditing),
+            )
+            .into(),
+            Section::Modes => self.mode_list(),
+            Section::Timers => self.timers_view(),
+        };
+        Some(element)
+    }
+
+    fn timers_view(&self) -> Element<'_, Message> {
+        let now = chrono::Local::now().timestamp();
+        let mut start = widget::row().spacing(4);
+        for minutes in TIMER_MINUTES {
+            start = start.push(
+                widget::button::standard(fl!("timer-minutes", minutes = minutes))
+                    .on_press(Message::StartTimer(minutes)),
+            );
+        }
+
+        let mut list = list_column();
+        for (index, due) in self.timers.iter().enumerate() {
+            let left = (due - now).max(0);
+            list = list.add(settings::item(
+                fl!(
+                    "timer-left",
+                    time = format!("{}:{:02}", left / 60, left % 60)
+                ),
+                widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                    .on_press(Message::CancelTimer(index)),
+            ));
+        }
+
+        let notification = settings::item(
+            fl!("timer-notification"),
+            widget::toggler(self.config.timer_notification).on_toggle(Message::TimerNotification),
+        );
+
+        widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("timers")))
+            .push(start)
+            .push(notification)
+            .push_maybe((!self.timers.is_empty()).then_some(list))
+            .into()
+    }
+
+    /// Blinks the keyboard to signal an expired timer, one half blink per
+    /// step, then restores the lighting it had before.
+    fn flash_step(&mut self, step: u8) -> Task<Message> {
+        let Some((mode, speed)) = self.flash_restore else {
+            return Task::none();
+        };
+        let Some(launch) = self.launch.as_mut() else {
+            self.flash_restore = None;
+            return Task::none();
+        };
+
+        let done = step + 1 >= FLASH_PULSES * 2;
+        let result = if step % 2 == 0 {
+            launch.set_led_mode(LedMode::Disabled, speed)
+        } else {
+            launch.set_led_mode(mode, speed)
+        };
+        if let Err(err) = result {
+            // Don't leave the keyboard dark halfway through a blink.
+            let restored = launch.set_led_mode(mode, speed);
+            self.log_event(format!("failed to flash keyboard: {err}"));
+            if let Err(err) = restored {
+                self.log_event(format!("failed to restore lighting after flash: {err}"));
+            }
+            self.flash_restore = None;
+            self.record_history(fl!("history-timer"));
+            return Task::none();
+        }
+        if done {
+            self.flash_restore = None;
+            // A completed flash ends where it started, so this usually only
+            // brings the history's baseline up to date.
+            self.record_history(fl!("history-timer"));
+            return Task::none();
+        }
+        cosmic::task::future(async move {
+            tokio::time::sleep(FLASH_STEP).await;
+            Message::FlashStep(step + 1)
+        })
+    }
+
+    fn modes_view(&self) -> Element<'_, Message> {
+        if self.arranging {
+            return self.arrangement_view();
+        }
+
+        let mut content = widget::column().spacing(8);
+        if self.quiet_restore.is_some() {
+            content = content.push(
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::body(fl!("quiet-hours-active")))
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::standard(fl!("override"))
+                            .on_press(Message::OverrideQuietHours),
+                    ),
+            );
+        }
+        if self.staged.is_some() {
+            content = content.push(
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::body(fl!("staged-changes")))
+                    .push(widget::horizontal_space())
+                    .push(widget::button::text(fl!("discard")).on_press(Message::DiscardStaged))
+                    .push(widget::button::suggested(fl!("apply")).on_press(Message::ApplyStaged)),
+            );
+        }
+        for section in &self.config.arrangement.0 {
+            content = content.push_maybe(self.section_view(*section));
+        }
+        content
+            .push(widget::button::text(fl!("customize")).on_press(Message::ToggleArranging))
+            .into()
+    }
+
+    /// Lets the user pick which sections the modes tab shows and in what
+    /// order. Hidden sections are listed after the shown ones.
+    fn arrangement_view(&self) -> Element<'_, Message> {
+        let shown = &self.config.arrangement.0;
+        let hidden = Section::ALL
+            .into_iter()
+            .filter(|section| !shown.contains(section));
+
+        let mut list = list_column();
+        for (index, section) in shown.iter().copied().chain(hidden).enumerate() {
+            let visible = index < shown.len();
+            list = list.add(settings::item(
+                section.label(),
+                widget::row()
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::button::icon(widget::icon::from_name("go-up-symbolic"))
+                            .on_press_maybe(
+                                (visible && index > 0).then_some(Message::MoveSection(section, -1)),
+                            ),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("go-down-symbolic"))
+                            .on_press_maybe(
+                                (visible && index + 1 < shown.len())
+                                    .then_some(Message::MoveSection(section, 1)),
+                            ),
+                    )
+                    .push(
+                        widget::toggler(visible)
+                            .on_toggle(move |visible| Message::ShowSection(section, visible)),
+                    ),
+            ));
+        }
+
+        widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("customize")))
+            .push(self.page_scrollable(list))
+            .push(settings::item(
+                fl!("popup-width"),
+                widget::dropdown(
+                    WIDTH_LABELS.as_slice(),
+                    PopupWidth::CHOICES
+                        .iter()
+                        .position(|width| *width == self.config.popup_width.0),
+                    Message::PopupWidthSelected,
+                ),
+            ))
+            .push(widget::button::standard(fl!("done")).on_press(Message::ToggleArranging))
+            .into()
+    }
+
+    fn scenes_view(&self) -> Element<'_, Message> {
+        let mut list = list_column();
+        for (index, scene) in self.config.scenes.iter().enumerate() {
+            let active = self.active_scene.as_deref() == Some(scene.name.as_str());
+            list = list.add(settings::item(
+                &scene.name,
+                widget::row()
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .push_maybe(
+                        active.then(|| widget::icon::from_name("object-select-symbolic").size(16)),
+                    )
+                    .push(
+                        widget::button::text(fl!("activate"))
+                            .on_press(Message::ActivateScene(index)),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::DeleteScene(index)),
+                    ),
+            ));
+        }
+
+        let save = widget::column()
+            .spacing(8)
+            .push(settings::item(
+                fl!("scene-profile"),
+                widget::dropdown(
+                    self.profile_names.as_slice(),
+                    self.scene_profile,
+                    Message::SceneProfileSelected,
+                ),
+            ))
+            .push(
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text_input(fl!("scene-name"), &self.scene_name)
+                            .on_input(Message::SceneNameChanged)
+                            .on_submit(|_| Message::SaveScene),
+                    )
+                    .push(widget::button::standard(fl!("save-scene")).on_press_maybe(
+                        (!self.scene_name.trim().is_empty()).then_some(Message::SaveScene),
+                    )),
+            )
+            .push(widget::text::caption(fl!("scene-description")));
+
+        let mut content = widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("scenes")));
+        if self.config.scenes.is_empty() {
+            content = content.push(widget::text::body(fl!("no-scenes")));
+        } else {
+            content = content.push(list);
+        }
+        content.push(save).into()
+    }
+
+    fn playlists_view(&self) -> Element<'_, Message> {
+        let mut list = list_column();
+        for (index, playlist) in self.config.playlists.iter().enumerate() {
+            let playing = self
+                .playback
+                .as_ref()
+                .is_some_and(|playback| playback.playlist == index);
+            let entries = playlist
+                .entries
+                .iter()
+                .map(|entry| {
+                    fl!(
+                        "playlist-entry",
+                        profile = entry.profile.as_str(),
+                        minutes = entry.seconds / 60
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let control = if playing {
+                widget::button::text(fl!("stop")).on_press(Message::StopPlaylist)
+            } else {
+                widget::button::text(fl!("play")).on_press(Message::PlayPlaylist(index))
+            };
+            list = list.add(
+                widget::column()
+                    .spacing(4)
+                    .push(settings::item(
+                        &playlist.name,
+                        widget::row().spacing(4).push(control).push(
+                            widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                                .on_press(Message::DeletePlaylist(index)),
+                        ),
+                    ))
+                    .push(widget::text::caption(entries)),
+            );
+        }
+
+        let add = widget::column()
+            .spacing(8)
+            .push(
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(widget::dropdown(
+                        self.profile_names.as_slice(),
+                        self.playlist_profile,
+                        Message::PlaylistProfileSelected,
+                    ))
+                    .push(widget::dropdown(
+                        PLAYLIST_LABELS.as_slice(),
+                        Some(self.playlist_minutes),
+                        Message::PlaylistMinutesSelected,
+                    )),
+            )
+            .push(
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text_input(fl!("playlist-name"), &self.playlist_name)
+                            .on_input(Message::PlaylistNameChanged)
+                            .on_submit(|_| Message::AddToPlaylist),
+                    )
+                    .push(
+                        widget::button::standard(fl!("add-to-playlist")).on_press_maybe(
+                            (!self.playlist_name.trim().is_empty()
+                                && self.playlist_profile.is_some())
+                            .then_some(Message::AddToPlaylist),
+                        ),
+                    ),
+            );
+
+        let mut content = widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("playlists")));
+        if self.config.playlists.is_empty() {
+            content = content.push(widget::text::body(fl!("no-playlists")));
+        } else {
+            content = content.push(list);
+        }
+        content.push(add).into()
+    }
+
+    /// Shows the current entry of the playing playlist and schedules the
+    /// next one. Entries whose profile was deleted are shown as a pause.
+    fn play_entry(&mut self) {
+        let Some(playback) = self.playback.as_ref() else {
+            return;
+        };
+        let Some(playlist) = self.config.playlists.get(playback.playlist) else {
+            self.playback = None;
+            return;
+        };
+        if playlist.entries.is_empty() {
+            return;
+        }
+        let source = fl!("history-playlist", name = playlist.name.as_str());
+        let entry = &playlist.entries[playback.entry % playlist.entries.len()];
+        let next_at = Instant::now() + Duration::from_secs(u64::from(entry.seconds));
+        let profile = self
+            .profile_named(&entry.profile)
+            .filter(|profile| !self.defer_to_quiet_hours(profile));
+
+        if let (Some(profile), Some(launch)) = (profile, self.launch.as_mut()) {
+            if let Err(err) = profile.apply(launch) {
+                self.log_event(format!("failed to play profile {}: {err}", profile.name));
+            }
+        }
+        self.record_history(source);
+        if let Some(playback) = self.playback.as_mut() {
+            playback.next_at = next_at;
+        }
+    }
+
+    fn stop_playlist(&mut self) {
+        if let Some(profile) = self.playback.take().and_then(|playback| playback.restore) {
+            self.apply_profile(&profile);
+            self.record_history(fl!("history-playlist-stopped"));
+        }
+    }
+
+    /// The profile behind an entry of the comparison dropdowns, where the
+    /// entry after the saved profiles is the keyboard's live state.
+    fn compare_side(&self, index: Option<usize>) -> Option<Profile> {
+        let index = index?;
+        if index == self.config.profiles.len() {
+            return self
+                .launch
+                .as_ref()
+                .map(|launch| Profile::from_launch(fl!("live-keyboard"), launch));
+        }
+        self.config.profiles.get(index).cloned()
+    }
+
+    fn compare_view(&self) -> Element<'_, Message> {
+        let pickers = widget::row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(widget::dropdown(
+                self.compare_options.as_slice(),
+                self.compare.0,
+                Message::CompareFirst,
+            ))
+            .push(widget::text::body("→"))
+            .push(widget::dropdown(
+                self.compare_options.as_slice(),
+                self.compare.1,
+                Message::CompareSecond,
+            ));
+
+        let mut content = widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("compare-profiles")))
+            .push(pickers);
+        if let (Some(first), Some(second)) = (
+            self.compare_side(self.compare.0),
+            self.compare_side(self.compare.1),
+        ) {
+            let changes = first.diff(&second);
+            if changes.is_empty() {
+                content = content.push(widget::text::body(fl!("profiles-identical")));
+            } else {
+                let mut list = list_column();
+                for change in &changes {
+                    list = list.add(widget::text::body(describe_change(change)));
+                }
+                content = content.push(list);
+            }
+        }
+        content.into()
+    }
+
+    fn profiles_view(&self) -> Element<'_, Message> {
+        let mut list = list_column();
+        let mut profiles: Vec<_> = self.config.profiles.iter().enumerate().collect();
+        if self.config.mode_order == ModeOrder::MostUsed {
+            // Stable sort, so profiles with equal counts keep their order.
+            profiles.sort_by_key(|(_, profile)| Reverse(self.config.profile_usage(&profile.name)));
+        }
+        let profiles = search::filter(&self.profile_search, profiles, |(_, profile)| {
+            profile.name.clone()
+        });
+        for (index, profile) in profiles {
+            list = list.add(settings::item(
+                &profile.name,
+                widget::row()
+                    .spacing(4)
+                    .push(widget::button::text(fl!("apply")).on_press(Message::ApplyProfile(index)))
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::Confirm(Confirmation::DeleteProfile {
+                                name: profile.name.clone(),
+                            })),
+                    ),
+            ));
+        }
+
+        let save = widget::row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(
+                widget::text_input(fl!("profile-name"), &self.profile_name)
+                    .on_input(Message::ProfileNameChanged)
+                    .on_submit(|_| Message::SaveProfile),
+            )
+            .push(
+                widget::button::standard(fl!("save-current")).on_press_maybe(
+                    (self.launch.is_some() && !self.profile_name.trim().is_empty())
+                        .then_some(Message::SaveProfile),
+                ),
+            );
+
+        let mut presets = list_column();
+        for (index, preset) in self.presets.iter().enumerate() {
+            presets = presets.add(settings::item(
+                &preset.name,
+                widget::row()
+                    .spacing(4)
+                    .push(widget::button::text(fl!("apply")).on_press(Message::ApplyPreset(index)))
+                    .push(
+                        widget::button::icon(widget::icon::from_name("list-add-symbolic"))
+                            .on_press(Message::AddPreset(index)),
+                    ),
+            ));
+        }
+
+        let mut content = widget::column().spacing(8);
+        if self.config.profiles.is_empty() {
+            content = content.push(widget::text::body(fl!("no-profiles")));
+        } else {
+            content = content.push(
+                widget::search_input(fl!("search-profiles"), &self.profile_search)
+                    .on_input(Message::ProfileSearchChanged),
+            );
+            content = content.push(list);
+        }
+        let exit_selected = |profile: Option<&String>| match profile {
+            Some(name) => self
+                .profile_names
+                .iter()
+                .position(|n| n == name)
+                .map(|index| index + 1),
+            None => Some(0),
+        };
+        let content = content
+            .push(save)
+            .push(settings::item(
+                fl!("exit-state"),
+                widget::dropdown(
+                    self.exit_options.as_slice(),
+                    exit_selected(self.config.exit_profile.as_ref()),
+                    Message::ExitProfileSelected,
+                ),
+            ))
+            .push(settings::item(
+                fl!("logout-state"),
+                widget::dropdown(
+                    self.exit_options.as_slice(),
+                    exit_selected(self.config.logout_profile.as_ref()),
+                    Message::LogoutProfileSelected,
+                ),
+            ))
+            .push(widget::text::heading(fl!("presets")))
+            .push(presets)
+            .push(self.compare_view())
+            .push(self.scenes_view())
+            .push(self.playlists_view());
+        self.page_scrollable(content)
+    }
 
-use cosmic::{Application, Element};
+    fn device_view(&self) -> Element<'_, Message> {
+        let Some(launch) = self.launch.as_ref() else {
+            return widget::text::body(fl!("no-device")).into();
+        };
 
-use tokio_udev::Device;
+        let mut info = list_column()
+            .add(settings::item(
+                fl!("board"),
+                widget::text::body(launch.board()),
+            ))
+            .add(settings::item(
+                fl!("firmware"),
+                widget::text::body(launch.version()),
+            ))
+            .add(settings::item(
+                fl!("max-brightness"),
+                widget::text::body(launch.max_brightness().to_string()),
+            ));
+        if let Some(snapshot) = self.snapshot.as_ref() {
+            let hex = snapshot.color.to_hex();
+            info = info.add(settings::item(
+                fl!("color"),
+                widget::row()
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::body(hex.clone()))
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-copy-symbolic"))
+                            .tooltip(fl!("copy-color"))
+                            .on_press(Message::CopyColor(hex)),
+                    ),
+            ));
+            let milliwatts =
+                power::estimate_milliwatts(launch.board(), snapshot, launch.max_brightness());
+            info = info.add(settings::item(
+                fl!("led-power"),
+                widget::text::body(fl!(
+                    "led-power-watts",
+                    watts = format::decimal(f64::from(milliwatts) / 1000.0, 1)
+                )),
+            ));
+        }
 
-use crate::fl;
+        let changes = match (self.login_snapshot.as_ref(), self.snapshot.as_ref()) {
+            (Some(login), Some(current)) => login.diff(current),
+            _ => Vec::new(),
+        };
+        let mut diff = list_column();
+        for change in &changes {
+            diff = diff.add(widget::text::body(describe_change(change)));
+        }
 
-#[derive(Default)]
-pub struct LaunchControl {
-    /// Application state which is managed by the COSMIC runtime.
-    core: Core,
-    /// The popup id.
-    popup: Option<Id>,
-    /// Example row toggler.
-    example_row: bool,
-}
+        let mut content = widget::column()
+            .spacing(8)
+            .push(info)
+            .push(widget::text::heading(fl!("changes-since-login")));
+        if changes.is_empty() {
+            content = content.push(widget::text::body(fl!("no-changes")));
+        } else {
+            content = content.push(diff);
+        }
+        let mut history = list_column();
+        let mut export = String::new();
+        for (timestamp, entry) in self.history.iter().rev() {
+            let line = format!("{}  {entry}", format_timestamp(*timestamp));
+            export.push_str(&line);
+            export.push('\n');
+            history = history.add(widget::text::body(line));
+        }
+        content = content.push(widget::text::heading(fl!("history")));
+        if self.history.is_empty() {
+            content = content.push(widget::text::body(fl!("history-empty")));
+        } else {
+            content = content.push(history).push(
+                widget::button::standard(fl!("copy-history"))
+                    .on_press(Message::CopyHistory(export)),
+            );
+        }
+        if !launch.quirks().is_empty() {
+            let mut quirks = list_column();
+            for quirk in launch.quirks() {
+                quirks = quirks.add(widget::text::body(describe_change(quirk)));
+            }
+            content = content
+                .push(widget::text::heading(fl!("firmware-quirks")))
+                .push(widget::text::caption(fl!("firmware-quirks-description")))
+                .push(quirks);
+        }
+        let cleaning = match self.cleaning_until {
+            Some(until) => {
+                let left = (until - chrono::Local::now().timestamp()).max(0);
+                widget::row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::body(fl!("cleaning-left", seconds = left)))
+                    .push(widget::button::standard(fl!("stop")).on_press(Message::StopCleaning))
+            }
+            None => widget::row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(widget::dropdown(
+                    CLEANING_LABELS.as_slice(),
+                    Some(self.cleaning_length),
+                    Message::CleaningLengthSelected,
+                ))
+                .push(widget::button::standard(fl!("start")).on_press(Message::StartCleaning)),
+        };
+        let lock_selected = match self.config.lock_profile.as_ref() {
+            Some(name) => self
+                .profile_names
+                .iter()
+                .position(|n| n == name)
+                .map(|index| index + 1),
+            None => Some(0),
+        };
+        let child_lock = list_column()
+            .add(settings::item(
+                fl!("child-lock"),
+                widget::toggler(self.config.child_lock).on_toggle(Message::ChildLock),
+            ))
+            .add(settings::item(
+                fl!("lock-profile"),
+                widget::dropdown(
+                    self.exit_options.as_slice(),
+                    lock_selected,
+                    Message::LockProfileSelected,
+                ),
+            ));
+        let quiet_hours = self.quiet_input.clone().unwrap_or_else(|| {
+            self.config
+                .quiet_hours
+                .map(QuietHours::describe)
+                .unwrap_or_default()
+        });
+        let mut child_lock = child_lock;
+        for controller in ExternalController::iter() {
+            child_lock = child_lock.add(settings::item(
+                fl!("yield-to", name = controller.name()),
+                widget::toggler(self.config.yield_to.0.contains(&controller))
+                    .on_toggle(move |enabled| Message::YieldTo(controller, enabled)),
+            ));
+        }
+        let child_lock = child_lock
+            .add(settings::item(
+                fl!("guest-mode"),
+                widget::toggler(self.guest.is_some()).on_toggle(Message::GuestMode),
+            ))
+            .add(settings::item(
+                fl!("demo-mode"),
+                widget::toggler(self.demo_restore.is_some()).on_toggle(Message::DemoMode),
+            ))
+            .add(settings::item(
+                fl!("quiet-hours"),
+                widget::text_input("22:00-07:00", quiet_hours)
+                    .on_input(Message::QuietHoursInput)
+                    .on_submit(|_| Message::QuietHoursSubmit),
+            ))
+            .add(settings::item(
+                fl!("brightness-cap"),
+                widget::dropdown(
+                    CAP_LABELS.as_slice(),
+                    BrightnessCap::CHOICES
+                        .iter()
+                        .position(|cap| *cap == self.config.brightness_cap.0),
+                    Message::BrightnessCapSelected,
+                ),
+            ));
+        let content = content
+            .push(widget::button::text(fl!("refresh")).on_press(Message::RefreshSnapshot))
+            .push(child_lock)
+            .push(widget::text::heading(fl!("cleaning-mode")))
+            .push(widget::text::caption(fl!("cleaning-description")))
+            .push(cleaning);
+        self.page_scrollable(content)
+    }
 
-pub struct DeviceInfo {
-    vid: u32,
-    pid: u32
-}
+    /// Leaves the keyboard to the first running program the user yields to,
+    /// or takes it back once none is left.
+    fn update_yield(&mut self) {
+        let target = self
+            .external
+            .iter()
+            .copied()
+            .find(|controller| self.config.yield_to.0.contains(controller));
+        match (self.yielded, target) {
+            (None, Some(controller)) => {
+                self.yielded = target;
+                self.open_launch();
+                self.log_event(format!("leaving the keyboard to {}", controller.name()));
+            }
+            (Some(controller), None) => {
+                self.yielded = None;
+                self.open_launch();
+                self.log_event(format!(
+                    "taking the keyboard back from {}",
+                    controller.name()
+                ));
+                self.refresh_snapshot();
+                self.active_rule = None;
+                self.apply_rules();
+                self.apply_pending();
+            }
+            _ => self.yielded = target,
+        }
+    }
 
-#[derive(Debug, Clone)]
-pub enum Message {
-    TogglePopup,
-    PopupClosed(Id),
-    ToggleExampleRow(bool),
-    DeviceConnected(DeviceInfo),
-    DeviceDisconnected
-}
+    /// Shows the next mode in demo mode. Modes that need a per-key layout or
+    /// turn the lighting off are skipped.
+    fn demo_step(&mut self) {
+        let Some(speed) = self.launch.as_ref().map(Launch::current_speed) else {
+            return;
+        };
+        let modes: Vec<LedMode> = LedMode::iter()
+            .filter(|mode| !matches!(mode, LedMode::PerKey | LedMode::Disabled | LedMode::Last))
+            .collect();
+        let mode = modes[self.demo_index % modes.len()];
+        self.demo_index += 1;
+        if self.defer_to_quiet_hours(&Profile::new(String::new(), mode, speed)) {
+            return;
+        }
+        let Some(launch) = self.launch.as_mut() else {
+            return;
+        };
+        if let Err(err) = launch.set_led_mode(mode, speed) {
+            self.log_event(format!("failed to show demo mode {mode}: {err}"));
+        }
+        self.record_history(fl!("history-demo"));
+    }
 
-impl LaunchControl {
-    async fn device_task(mut out: Subscription<Self::Message>) {
-        if let Ok(mut rx) = DeviceListener::new(0x3384, 0x0001..=0x000A)
-            .with_subsystem("hidraw")
-            .with_debounce_ms(300)
-            .start()
-            .await
-        {
-            while let Some(ev) = rx.recv().await {
-                // forward events into iced
-                let _ = out.send(Message::Device(ev)).await;
+    /// Blocks or unblocks key input on the keyboard, logging failures.
+    fn set_input_blocked(&mut self, blocked: bool) -> bool {
+        let Some(launch) = self.launch.as_mut() else {
+            return false;
+        };
+        if let Err(err) = launch.set_input_blocked(blocked) {
+            self.log_event(format!("failed to change key input blocking: {err}"));
+            return false;
+        }
+        true
+    }
+
+    fn diagnostics_view(&self) -> Element<'_, Message> {
+        if let Some(report) = self.stress_run.as_ref() {
+            return widget::column()
+                .spacing(8)
+                .push(widget::text::heading(fl!("ec-stress")))
+                .push(widget::button::standard(fl!(
+                    "stressing",
+                    cycles = report.cycles
+                )))
+                .into();
+        }
+        let Some(launch) = self.launch.as_ref() else {
+            return widget::text::body(fl!("no-device")).into();
+        };
+
+        let usb = match launch.usb() {
+            Some(usb) => list_column()
+                .add(settings::item(
+                    fl!("usb-port"),
+                    widget::text::body(&usb.port_path),
+                ))
+                .add(settings::item(
+                    fl!("usb-speed"),
+                    widget::text::body(usb.speed_label()),
+                ))
+                .add(settings::item(
+                    fl!("usb-hubs"),
+                    widget::text::body(usb.hubs.join(" → ")),
+                )),
+            None => list_column().add(widget::text::body(fl!("usb-unknown"))),
+        };
+        let mut content = widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("usb-connection")))
+            .push(usb);
+        if launch.usb().is_some_and(|usb| usb.behind_hub()) {
+            content = content.push(widget::text::caption(fl!("usb-hub-hint")));
+        }
+
+        let measure = match self.latency_run.as_ref() {
+            Some(samples) => widget::button::standard(fl!(
+                "measuring-latency",
+                done = samples.len(),
+                total = LATENCY_SAMPLES
+            )),
+            None => {
+                widget::button::standard(fl!("measure-latency")).on_press(Message::MeasureLatency)
             }
+        };
+
+        content = content
+            .push(widget::text::heading(fl!("ec-latency")))
+            .push(measure);
+        if let Some(report) = self.latency.as_ref() {
+            content = content.push(
+                list_column()
+                    .add(settings::item(
+                        "p50",
+                        widget::text::body(format_ms(report.p50)),
+                    ))
+                    .add(settings::item(
+                        "p95",
+                        widget::text::body(format_ms(report.p95)),
+                    ))
+                    .add(settings::item(
+                        "p99",
+                        widget::text::body(format_ms(report.p99)),
+                    ))
+                    .add(settings::item(
+                        fl!("latency-max"),
+                        widget::text::body(format_ms(report.max)),
+                    )),
+            );
+        }
+
+        let stress = widget::button::standard(fl!("start-stress")).on_press(Message::StartStress);
+        content = content.push(widget::text::heading(fl!("ec-stress"))).push(
+            widget::row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(widget::dropdown(
+                    STRESS_LABELS.as_slice(),
+                    Some(self.stress_length),
+                    Message::StressLengthSelected,
+                ))
+                .push(stress),
+        );
+        if let Some(report) = self.stress.as_ref() {
+            content = content.push(
+                list_column()
+                    .add(settings::item(
+                        fl!("stress-cycles"),
+                        widget::text::body(report.cycles.to_string()),
+                    ))
+                    .add(settings::item(
+                        fl!("stress-retries"),
+                        widget::text::body(report.retries.to_string()),
+                    ))
+                    .add(settings::item(
+                        fl!("stress-errors"),
+                        widget::text::body(format!(
+                            "{} ({}%)",
+                            report.errors,
+                            format::decimal(report.error_rate() * 100.0, 2)
+                        )),
+                    )),
+            );
+        }
+
+        let total = self.metrics.ec(Some(launch.stats()));
+        content = content
+            .push(widget::text::heading(fl!("metrics")))
+            .push(
+                list_column()
+                    .add(settings::item(
+                        fl!("metrics-commands"),
+                        widget::text::body(format!(
+                            "{} ({}/s)",
+                            total.commands,
+                            format::decimal(self.metrics.commands_per_second(total), 1)
+                        )),
+                    ))
+                    .add(settings::item(
+                        fl!("metrics-errors"),
+                        widget::text::body(total.errors.to_string()),
+                    ))
+                    .add(settings::item(
+                        fl!("metrics-reconnects"),
+                        widget::text::body(self.metrics.reconnects.to_string()),
+                    ))
+                    .add(settings::item(
+                        fl!("link-health"),
+                        widget::text::body(describe_health(launch.health())),
+                    )),
+            )
+            .push(
+                widget::button::standard(fl!("copy-metrics"))
+                    .on_press(Message::CopyMetrics(self.metrics.to_prometheus(total))),
+            );
+
+        let mut log = list_column();
+        for (timestamp, entry) in self.event_log.iter().rev() {
+            log = log.add(widget::text::body(format!(
+                "{}  {entry}",
+                format_timestamp(*timestamp)
+            )));
+        }
+        content = content.push(widget::text::heading(fl!("event-log")));
+        if self.event_log.is_empty() {
+            content = content.push(widget::text::body(fl!("event-log-empty")));
+        } else {
+            content = content.push(log);
         }
+        self.page_scrollable(content)
     }
-}
 
+    fn rules_view(&self) -> Element<'_, Message> {
+        let mut list = list_column();
+        for (index, rule) in self.config.rules.iter().enumerate() {
+            let description = fl!(
+                "rule-summary",
+                trigger = rule.trigger.describe(),
+                profile = rule.profile.as_str(),
+                priority = rule.priority
+            );
+            let skip = if rule.is_skipped(&self.context) {
+                let until = rule.skip_until.map(format_timestamp).unwrap_or_default();
+                Some(
+                    widget::button::text(fl!("resume-rule", until = until))
+                        .on_press(Message::ResumeRule(index)),
+                )
+            } else {
+                rule.skip_target(&self.context).map(|_| {
+                    widget::button::text(fl!("skip-rule")).on_press(Message::SkipRule(index))
+                })
+            };
+            list = list.add(
+                widget::row()
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .push(widget::text::body(description).width(Length::Fill))
+                    .push_maybe(skip)
+                    .push(
+                        widget::button::icon(widget::icon::from_name("go-up-symbolic"))
+                            .on_press(Message::MoveRule(index, 1)),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("go-down-symbolic"))
+                            .on_press(Message::MoveRule(index, -1)),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::DeleteRule(index)),
+                    )
+                    .push(
+                        widget::toggler(rule.enabled)
+                            .on_toggle(move |enabled| Message::ToggleRule(index, enabled)),
+                    ),
+            );
+        }
+
+        let kind = TriggerKind::all()[self.rule_draft.kind];
+        let mut editor = widget::column().spacing(8).push(settings::item(
+            fl!("rule-trigger"),
+            widget::dropdown(
+                TRIGGER_LABELS.as_slice(),
+                Some(self.rule_draft.kind),
+                Message::RuleKindSelected,
+            ),
+        ));
+        if kind.has_param() {
+            let placeholder = match kind {
+                TriggerKind::TimeRange => fl!("rule-param-time-range"),
+                TriggerKind::Script => fl!("rule-param-script"),
+                _ => fl!("rule-param-app-id"),
+            };
+            editor = editor.push(
+                widget::text_input(placeholder, &self.rule_draft.param)
+                    .on_input(Message::RuleParamChanged),
+            );
+        }
+        if kind == TriggerKind::TimeRange {
+            editor = editor.push(settings::item(
+                fl!("rule-days"),
+                widget::dropdown(
+                    DAYS_LABELS.as_slice(),
+                    Some(self.rule_draft.days),
+                    Message::RuleDaysSelected,
+                ),
+            ));
+        }
+        editor = editor
+            .push(settings::item(
+                fl!("rule-profile"),
+                widget::dropdown(
+                    self.profile_names.as_slice(),
+                    self.rule_draft.profile,
+                    Message::RuleProfileSelected,
+                ),
+            ))
+            .push(
+                widget::button::standard(fl!("add-rule")).on_press_maybe(
+                    (self.rule_draft.profile.is_some()
+                        && kind
+                            .build(&self.rule_draft.param, self.rule_draft.days())
+                            .is_some())
+                    .then_some(Message::AddRule),
+                ),
+            );
+
+        let mut content = widget::column().spacing(8);
+        if self.config.rules.is_empty() {
+            content = content.push(widget::text::body(fl!("no-rules")));
+        } else {
+            content = content.push(self.page_scrollable(list));
+        }
+        content
+            .push(editor)
+            .push(widget::button::text(fl!("explain-rules")).on_press(Message::ExplainRules))
+            .push_maybe(self.explanation.as_deref().map(widget::text::caption))
+            .push(self.simulation_view())
+            .into()
+    }
+
+    /// Lets the user describe a situation and see which rule would win in
+    /// it, without touching the keyboard.
+    fn simulation_view(&self) -> Element<'_, Message> {
+        let simulation = &self.simulation;
+        let fields = list_column()
+            .add(settings::item(
+                fl!("trigger-app-focused"),
+                widget::text_input(
+                    fl!("rule-param-app-id"),
+                    simulation.focused_app.as_deref().unwrap_or_default(),
+                )
+                .on_input(Message::SimulatedApp),
+            ))
+            .add(settings::item(
+                fl!("simulate-time"),
+                widget::text_input(fl!("simulate-time-now"), &self.simulation_time)
+                    .on_input(Message::SimulatedTime),
+            ))
+            .add(settings::item(
+                fl!("days-weekends"),
+                widget::toggler(simulation.weekend).on_toggle(Message::SimulatedWeekend),
+            ))
+            .add(settings::item(
+                fl!("trigger-on-battery"),
+                widget::toggler(simulation.on_battery).on_toggle(Message::SimulatedBattery),
+            ))
+            .add(settings::item(
+                fl!("trigger-locked"),
+                widget::toggler(simulation.locked).on_toggle(Message::SimulatedLocked),
+            ))
+            .add(settings::item(
+                fl!("trigger-do-not-disturb"),
+                widget::toggler(simulation.do_not_disturb).on_toggle(Message::SimulatedDnd),
+            ));
+
+        widget::column()
+            .spacing(8)
+            .push(widget::text::heading(fl!("simulate")))
+            .push(fields)
+            .push(
+                widget::button::standard(fl!("simulate-run")).on_press_maybe(
+                    (self.simulation_time.trim().is_empty()
+                        || rules::parse_time(&self.simulation_time).is_some())
+                    .then_some(Message::Simulate),
+                ),
+            )
+            .into()
+    }
+}
 
 impl Application for LaunchControl {
     type Executor = cosmic::executor::Default;
@@ -71,14 +2564,117 @@ impl Application for LaunchControl {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        channel("device-listener", 128, LaunchControl::device_task)
+        // Tally-light style rules should react within seconds, not minutes.
+        let realtime_rules = self
+            .config
+            .rules
+            .iter()
+            .any(|rule| rule.enabled && rule.trigger.is_realtime());
+        // Pollers only run while something is shown or automation needs
+        // them, so an idle applet sends nothing to the keyboard.
+        let shown = !self.surfaces.is_empty();
+        let automated =
+            self.config.quiet_hours.is_some() || self.config.rules.iter().any(|rule| rule.enabled);
+        let yielding = !self.config.yield_to.0.is_empty();
+
+        Subscription::batch([
+            bus_events(&self.bus).map(Message::Bus),
+            Subscription::run_with_id("device-listener", {
+                let bus = self.bus.clone();
+                stream::channel(1, |_| {
+                    supervisor::supervise("device listener", bus, |bus| {
+                        DeviceListener::new().run(bus)
+                    })
+                })
+            }),
+            Subscription::run_with_id("shutdown", {
+                let bus = self.bus.clone();
+                stream::channel(1, |_| shutdown::run(bus))
+            }),
+            Subscription::run_with_id("kernel-log", {
+                let bus = self.bus.clone();
+                stream::channel(1, |_| kmsg::run(bus))
+            }),
+            self.core
+                .watch_config::<Config>(Self::APP_ID)
+                .map(|update| Message::UpdateConfig(update.config)),
+            self.core
+                .watch_config::<NotificationsConfig>(NOTIFICATIONS_ID)
+                .map(|update| Message::DndChanged(update.config.do_not_disturb)),
+            if shown || automated {
+                time::every(Duration::from_secs(if realtime_rules { 2 } else { 60 }))
+                    .map(|_| Message::EvaluateRules)
+            } else {
+                Subscription::none()
+            },
+            if shown || yielding {
+                time::every(CONFLICT_CHECK).map(|_| Message::CheckConflicts)
+            } else {
+                Subscription::none()
+            },
+            if shown && self.launch.is_some() {
+                time::every(STATE_REFRESH).map(|_| Message::PollState)
+            } else {
+                Subscription::none()
+            },
+            if self.timers.is_empty() && self.cleaning_until.is_none() && self.playback.is_none() {
+                Subscription::none()
+            } else {
+                time::every(Duration::from_secs(1)).map(|_| Message::TimerTick)
+            },
+            if self.demo_restore.is_some() {
+                time::every(DEMO_STEP).map(|_| Message::DemoStep)
+            } else {
+                Subscription::none()
+            },
+        ])
     }
 
     fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        let app = LaunchControl {
+        let config_handler = cosmic_config::Config::new(Self::APP_ID, Config::VERSION).ok();
+        let config = config_handler
+            .as_ref()
+            .map(|context| match Config::get_entry(context) {
+                Ok(config) => config,
+                Err((_errors, config)) => config,
+            })
+            .unwrap_or_default();
+
+        let notifications_handler =
+            cosmic_config::Config::new(NOTIFICATIONS_ID, NotificationsConfig::VERSION).ok();
+        let dnd = notifications_handler
+            .as_ref()
+            .and_then(|context| NotificationsConfig::get_entry(context).ok())
+            .is_some_and(|notifications| notifications.do_not_disturb);
+
+        let mut tabs: segmented_button::SingleSelectModel = segmented_button::Model::builder()
+            .insert(|b| b.text(fl!("tab-modes")).data(Tab::Modes))
+            .insert(|b| b.text(fl!("tab-profiles")).data(Tab::Profiles))
+            .insert(|b| b.text(fl!("tab-rules")).data(Tab::Rules))
+            .insert(|b| b.text(fl!("tab-device")).data(Tab::Device))
+            .insert(|b| b.text(fl!("tab-diagnostics")).data(Tab::Diagnostics))
+            .build();
+        let last_tab = tabs
+            .iter()
+            .find(|entity| tabs.data::<Tab>(*entity) == Some(&config.last_tab))
+            .or_else(|| tabs.iter().next());
+        if let Some(entity) = last_tab {
+            tabs.activate(entity);
+        }
+
+        let mut app = LaunchControl {
             core,
+            tabs,
+            config_handler,
+            notifications_handler,
+            dnd,
+            presets: BundledPresets.presets(),
             ..Default::default()
         };
+        app.set_config(config);
+        app.open_launch();
+        app.restore_journal();
+        app.apply_rules();
 
         (app, Task::none())
     }
@@ -87,7 +2683,6 @@ impl Application for LaunchControl {
         Some(Message::PopupClosed(id))
     }
 
-
     fn view(&self) -> Element<'_, Self::Message> {
         self.core
             .applet
@@ -96,26 +2691,66 @@ impl Application for LaunchControl {
             .into()
     }
 
-    fn view_window(&self, _id: Id) -> Element<'_, Self::Message> {
-        let content_list = list_column()
-            .padding(5)
-            .spacing(0)
-            .add(settings::item(
-                fl!("example-row"),
-                widget::toggler(self.example_row).on_toggle(Message::ToggleExampleRow),
-            ));
-
-        self.core.applet.popup_container(content_list).into()
+    fn view_window(&self, id: Id) -> Element<'_, Self::Message> {
+        match self.surfaces.get(&id) {
+            Some(Surface::Popup) => {
+                let content = widget::column()
+                    .padding(5)
+                    .spacing(8)
+                    .push_maybe(self.notices())
+                    .push(self.quick_view());
+                self.core.applet.popup_container(content).into()
+            }
+            Some(Surface::Editor) => {
+                let page = match self.active_tab() {
+                    Tab::Modes => self.modes_view(),
+                    Tab::Profiles => self.profiles_view(),
+                    Tab::Rules => self.rules_view(),
+                    Tab::Device => self.device_view(),
+                    Tab::Diagnostics => self.diagnostics_view(),
+                };
+                let content = widget::column()
+                    .spacing(8)
+                    .push_maybe(self.notices())
+                    .push(
+                        widget::tab_bar::horizontal(&self.tabs).on_activate(Message::TabActivated),
+                    )
+                    .push(page);
+                widget::container(content).padding(16).into()
+            }
+            Some(Surface::Confirm(confirmation)) => {
+                let buttons = widget::row()
+                    .spacing(8)
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Answer(id, false)),
+                    )
+                    .push(
+                        widget::button::destructive(confirmation.action_label())
+                            .on_press(Message::Answer(id, true)),
+                    );
+                let content = widget::column()
+                    .spacing(16)
+                    .push(widget::text::body(confirmation.question()))
+                    .push(buttons);
+                widget::container(content).padding(16).into()
+            }
+            None => widget::horizontal_space().into(),
+        }
     }
 
     fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
         match message {
             Message::TogglePopup => {
-                return if let Some(p) = self.popup.take() {
+                return if let Some(p) = self.surface(Surface::Popup) {
+                    self.surfaces.remove(&p);
                     destroy_popup(p)
                 } else {
+                    self.sync_external_changes();
                     let new_id = Id::unique();
-                    self.popup.replace(new_id);
+                    self.surfaces.insert(new_id, Surface::Popup);
+                    self.search.clear();
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
                         new_id,
@@ -124,19 +2759,831 @@ impl Application for LaunchControl {
                         None,
                     );
                     popup_settings.positioner.size_limits = Limits::NONE
-                        .max_width(372.0)
+                        .max_width(f32::from(self.config.popup_width.0))
                         .min_width(300.0)
                         .min_height(200.0)
                         .max_height(1080.0);
-                    get_popup(popup_settings)
+                    Task::batch([
+                        get_popup(popup_settings),
+                        widget::text_input::focus(SEARCH_ID.clone()),
+                    ])
                 }
             }
             Message::PopupClosed(id) => {
-                if self.popup.as_ref() == Some(&id) {
-                    self.popup = None;
+                self.surfaces.remove(&id);
+            }
+            Message::Confirm(confirmation) => {
+                let (id, open) = window::open(window::Settings {
+                    size: Size::new(360.0, 140.0),
+                    resizable: false,
+                    ..Default::default()
+                });
+                self.surfaces.insert(id, Surface::Confirm(confirmation));
+                return open.discard();
+            }
+            Message::Answer(id, accepted) => {
+                let Some(Surface::Confirm(confirmation)) = self.surfaces.remove(&id) else {
+                    return Task::none();
+                };
+                let close = window::close(id);
+                if accepted {
+                    return Task::batch([close, self.update(confirmation.message())]);
+                }
+                return close;
+            }
+            Message::SaveToKeyboard => {
+                // Guest mode keeps nothing, least of all in the keyboard's flash.
+                if self.guest.is_some() {
+                    return Task::none();
+                }
+                if let Some(launch) = self.launch.as_mut() {
+                    match launch.save_to_keyboard() {
+                        Ok(()) => self.log_event(String::from("saved lighting to the keyboard")),
+                        Err(err) => self
+                            .log_event(format!("failed to save lighting to the keyboard: {err}")),
+                    }
+                }
+            }
+            Message::OpenWindow => {
+                let close_popup = match self.surface(Surface::Popup) {
+                    Some(popup) => {
+                        self.surfaces.remove(&popup);
+                        destroy_popup(popup)
+                    }
+                    None => Task::none(),
+                };
+                if let Some(id) = self.surface(Surface::Editor) {
+                    return Task::batch([close_popup, window::gain_focus(id)]);
+                }
+                let (id, open) = window::open(window::Settings {
+                    size: Size::new(640.0, 720.0),
+                    min_size: Some(Size::new(372.0, 300.0)),
+                    resizable: true,
+                    ..Default::default()
+                });
+                self.surfaces.insert(id, Surface::Editor);
+                return Task::batch([close_popup, open.discard(), self.restore_scroll()]);
+            }
+            Message::TabActivated(entity) => {
+                self.tabs.activate(entity);
+                let tab = self.active_tab();
+                if tab == Tab::Device {
+                    self.refresh_snapshot();
+                }
+                self.write_config(tab, |config| &mut config.last_tab, Config::set_last_tab);
+                return self.restore_scroll();
+            }
+            Message::PageScrolled(offset) => {
+                self.scroll_offsets.insert(self.active_tab(), offset);
+            }
+            Message::UpdateConfig(config) => match self.guest.as_mut() {
+                Some(guest) => guest.config = config,
+                None => self.set_config(config),
+            },
+            Message::Bus(event) => self.handle_event(event),
+            Message::SearchChanged(search) => self.search = search,
+            Message::ProfileSearchChanged(search) => self.profile_search = search,
+            Message::SearchSubmitted => {
+                if let Some(mode) = self.filtered_modes().first().copied() {
+                    self.set_mode(mode);
+                }
+            }
+            Message::SetMode(mode) => self.set_mode(mode),
+            Message::SortByUsage(enabled) => {
+                let order = if enabled {
+                    ModeOrder::MostUsed
+                } else {
+                    ModeOrder::Default
+                };
+                self.write_config(
+                    order,
+                    |config| &mut config.mode_order,
+                    Config::set_mode_order,
+                );
+            }
+            Message::StagedEditing(enabled) => {
+                self.write_config(
+                    enabled,
+                    |config| &mut config.staged_editing,
+                    Config::set_staged_editing,
+                );
+                if !enabled {
+                    self.apply_staged();
+                }
+            }
+            Message::ApplyStaged => self.apply_staged(),
+            Message::DiscardStaged => self.staged = None,
+            Message::ProfileNameChanged(name) => self.profile_name = name,
+            Message::SaveProfile => {
+                let name = self.profile_name.trim().to_string();
+                if let Some(launch) = self.launch.as_ref().filter(|_| !name.is_empty()) {
+                    let profile = Profile::from_launch(name, launch);
+                    self.save_profile(profile);
+                    self.profile_name.clear();
                 }
             }
-            Message::ToggleExampleRow(toggled) => self.example_row = toggled,
+            Message::ApplyProfile(index) => {
+                if let Some(profile) = self.config.profiles.get(index).cloned() {
+                    self.apply_profile(&profile);
+                    let mut usage = self.config.profile_usage.clone();
+                    *usage.entry(profile.name).or_default() += 1;
+                    self.write_config(
+                        usage,
+                        |config| &mut config.profile_usage,
+                        Config::set_profile_usage,
+                    );
+                }
+            }
+            Message::SceneNameChanged(name) => self.scene_name = name,
+            Message::SceneProfileSelected(index) => self.scene_profile = Some(index),
+            Message::SaveScene => {
+                let name = self.scene_name.trim().to_string();
+                if !name.is_empty() {
+                    let profile = self
+                        .scene_profile
+                        .and_then(|index| self.profile_names.get(index).cloned());
+                    let scene = Scene::capture(name, profile, &self.config.rules);
+                    let mut scenes = self.config.scenes.clone();
+                    match scenes.iter_mut().find(|s| s.name == scene.name) {
+                        Some(existing) => *existing = scene,
+                        None => scenes.push(scene),
+                    }
+                    self.write_config(scenes, |config| &mut config.scenes, Config::set_scenes);
+                    self.scene_name.clear();
+                    self.scene_profile = None;
+                }
+            }
+            Message::ActivateScene(index) => {
+                if let Some(scene) = self.config.scenes.get(index).cloned() {
+                    self.set_rules(scene.apply_to(&self.config.rules));
+                    let profile = scene
+                        .profile
+                        .as_ref()
+                        .and_then(|name| self.config.profiles.iter().find(|p| &p.name == name))
+                        .cloned();
+                    if let Some(profile) = profile {
+                        self.apply_profile(&profile);
+                    }
+                    self.active_scene = Some(scene.name);
+                }
+            }
+            Message::DeleteScene(index) => {
+                let mut scenes = self.config.scenes.clone();
+                if index < scenes.len() {
+                    let removed = scenes.remove(index);
+                    if self.active_scene.as_ref() == Some(&removed.name) {
+                        self.active_scene = None;
+                    }
+                    self.write_config(scenes, |config| &mut config.scenes, Config::set_scenes);
+                }
+            }
+            Message::ApplyPreset(index) => {
+                if let Some(preset) = self.presets.get(index).cloned() {
+                    self.apply_profile(&preset);
+                }
+            }
+            Message::AddPreset(index) => {
+                if let Some(preset) = self.presets.get(index).cloned() {
+                    self.save_profile(preset);
+                }
+            }
+            Message::DeleteProfile(name) => {
+                let mut profiles = self.config.profiles.clone();
+                if let Some(index) = profiles.iter().position(|p| p.name == name) {
+                    let removed = profiles.remove(index);
+                    self.write_config(
+                        profiles,
+                        |config| &mut config.profiles,
+                        Config::set_profiles,
+                    );
+                    let rules = self
+                        .config
+                        .rules
+                        .iter()
+                        .filter(|rule| rule.profile != removed.name)
+                        .cloned()
+                        .collect();
+                    self.set_rules(rules);
+                    let mut usage = self.config.profile_usage.clone();
+                    if usage.remove(&removed.name).is_some() {
+                        self.write_config(
+                            usage,
+                            |config| &mut config.profile_usage,
+                            Config::set_profile_usage,
+                        );
+                    }
+                    self.sync_profile_names();
+                    // Selections are indices into the profile list, so follow
+                    // the entries after the removed one.
+                    for selected in [
+                        &mut self.rule_draft.profile,
+                        &mut self.playlist_profile,
+                        &mut self.scene_profile,
+                        &mut self.compare.0,
+                        &mut self.compare.1,
+                    ] {
+                        *selected = selected.and_then(|selected| match selected.cmp(&index) {
+                            std::cmp::Ordering::Less => Some(selected),
+                            std::cmp::Ordering::Equal => None,
+                            std::cmp::Ordering::Greater => Some(selected - 1),
+                        });
+                    }
+                }
+            }
+            Message::RuleKindSelected(kind) => {
+                self.rule_draft.kind = kind;
+                self.rule_draft.param.clear();
+            }
+            Message::RuleParamChanged(param) => self.rule_draft.param = param,
+            Message::RuleProfileSelected(index) => self.rule_draft.profile = Some(index),
+            Message::AddRule => {
+                let kind = TriggerKind::all()[self.rule_draft.kind];
+                let profile = self
+                    .rule_draft
+                    .profile
+                    .and_then(|index| self.profile_names.get(index));
+                let trigger = kind.build(&self.rule_draft.param, self.rule_draft.days());
+                if let (Some(trigger), Some(profile)) = (trigger, profile) {
+                    let mut rules = self.config.rules.clone();
+                    rules.push(Rule {
+                        trigger,
+                        profile: profile.clone(),
+                        priority: 0,
+                        enabled: true,
+                        skip_until: None,
+                    });
+                    self.set_rules(rules);
+                    self.rule_draft = RuleDraft::default();
+                }
+            }
+            Message::ToggleRule(index, enabled) => {
+                let mut rules = self.config.rules.clone();
+                if let Some(rule) = rules.get_mut(index) {
+                    rule.enabled = enabled;
+                    self.set_rules(rules);
+                }
+            }
+            Message::MoveRule(index, delta) => {
+                let mut rules = self.config.rules.clone();
+                if let Some(rule) = rules.get_mut(index) {
+                    rule.priority += delta;
+                    self.set_rules(rules);
+                }
+            }
+            Message::DeleteRule(index) => {
+                let mut rules = self.config.rules.clone();
+                if index < rules.len() {
+                    rules.remove(index);
+                    self.set_rules(rules);
+                }
+            }
+            Message::RuleDaysSelected(days) => self.rule_draft.days = days,
+            Message::SkipRule(index) => {
+                let mut rules = self.config.rules.clone();
+                if let Some(rule) = rules.get_mut(index) {
+                    rule.skip_until = rule.skip_target(&self.context);
+                    // Let whatever else applies now take over.
+                    self.set_rules(rules);
+                }
+            }
+            Message::ResumeRule(index) => {
+                let mut rules = self.config.rules.clone();
+                if let Some(rule) = rules.get_mut(index) {
+                    rule.skip_until = None;
+                    self.set_rules(rules);
+                }
+            }
+            Message::EvaluateRules => {
+                // Scripts, device scans and logind can be slow, so they only
+                // run when a rule needs them and never on the UI thread.
+                let sources = rules::indicator_sources(&self.config.rules);
+                let uses = |kind| rules::uses(&self.config.rules, kind);
+                let microphone = uses(TriggerKind::MicrophoneInUse);
+                let camera = uses(TriggerKind::CameraInUse);
+                let locked = uses(TriggerKind::Locked);
+                if sources.is_empty() && !microphone && !camera && !locked {
+                    self.indicators.clear();
+                    self.device_usage = DeviceUsage::default();
+                    self.locked = false;
+                    self.apply_rules();
+                } else {
+                    return cosmic::task::future(async move {
+                        let (readings, usage, locked) = tokio::join!(
+                            indicators::read_all(sources),
+                            DeviceUsage::read(microphone, camera),
+                            async move { locked && session::locked().await }
+                        );
+                        Message::RuleInputsRead(readings, usage, locked)
+                    });
+                }
+            }
+            Message::RuleInputsRead(readings, usage, locked) => {
+                self.indicators = readings;
+                self.device_usage = usage;
+                self.locked = locked;
+                self.apply_rules();
+            }
+            Message::RefreshSnapshot => self.refresh_snapshot(),
+            Message::CopyColor(hex) => return cosmic::iced::clipboard::write(hex),
+            Message::CopyMetrics(text) => return cosmic::iced::clipboard::write(text),
+            Message::CopyHistory(text) => return cosmic::iced::clipboard::write(text),
+            Message::DismissTaskFailure => self.task_failure = None,
+            Message::CompareFirst(index) => self.compare.0 = Some(index),
+            Message::ChildLock(locked) => self.set_child_lock(locked),
+            Message::QuietHoursInput(text) => self.quiet_input = Some(text),
+            Message::QuietHoursSubmit => {
+                let Some(text) = self.quiet_input.take() else {
+                    return Task::none();
+                };
+                let quiet_hours = if text.trim().is_empty() {
+                    None
+                } else if let Some(quiet_hours) = QuietHours::parse(&text) {
+                    Some(quiet_hours)
+                } else {
+                    self.quiet_input = Some(text);
+                    return Task::none();
+                };
+                self.write_config(
+                    quiet_hours,
+                    |config| &mut config.quiet_hours,
+                    Config::set_quiet_hours,
+                );
+                self.apply_rules();
+            }
+            Message::GuestMode(true) => {
+                if self.guest.is_some() {
+                    return Task::none();
+                }
+                self.guest = Some(Guest {
+                    config: self.config.clone(),
+                    lighting: self
+                        .launch
+                        .as_ref()
+                        .map(|launch| Profile::from_launch(String::new(), launch)),
+                });
+            }
+            Message::GuestMode(false) => {
+                if let Some(guest) = self.guest.take() {
+                    let locked = self.config.child_lock;
+                    self.set_config(guest.config);
+                    if let Some(profile) = guest.lighting {
+                        self.apply_profile(&profile);
+                    }
+                    // Bring the keyboard in line with the child lock setting
+                    // that is back in effect.
+                    if self.config.child_lock {
+                        self.enforce_child_lock();
+                    } else if locked {
+                        self.lock_restore = None;
+                        self.set_input_blocked(false);
+                    }
+                }
+            }
+            Message::DemoMode(enabled) => {
+                if enabled {
+                    self.demo_restore = self
+                        .launch
+                        .as_ref()
+                        .map(|launch| Profile::from_launch(String::new(), launch));
+                    self.demo_index = 0;
+                    self.demo_step();
+                } else if let Some(profile) = self.demo_restore.take() {
+                    self.apply_profile(&profile);
+                    self.record_history(fl!("history-demo"));
+                }
+            }
+            Message::DemoStep => self.demo_step(),
+            Message::PlaylistNameChanged(name) => self.playlist_name = name,
+            Message::PlaylistProfileSelected(index) => self.playlist_profile = Some(index),
+            Message::PlaylistMinutesSelected(index) => self.playlist_minutes = index,
+            Message::AddToPlaylist => {
+                let name = self.playlist_name.trim().to_string();
+                let profile = self
+                    .playlist_profile
+                    .and_then(|index| self.profile_names.get(index))
+                    .cloned();
+                let Some(profile) = profile.filter(|_| !name.is_empty()) else {
+                    return Task::none();
+                };
+                let entry = PlaylistEntry {
+                    profile,
+                    seconds: PLAYLIST_MINUTES[self.playlist_minutes] * 60,
+                };
+                let mut playlists = self.config.playlists.clone();
+                match playlists.iter_mut().find(|playlist| playlist.name == name) {
+                    Some(playlist) => playlist.entries.push(entry),
+                    None => playlists.push(Playlist {
+                        name,
+                        entries: vec![entry],
+                    }),
+                }
+                self.write_config(
+                    playlists,
+                    |config| &mut config.playlists,
+                    Config::set_playlists,
+                );
+            }
+            Message::PlayPlaylist(index) => {
+                let restore = match self.playback.take() {
+                    Some(playback) => playback.restore,
+                    None => self
+                        .launch
+                        .as_ref()
+                        .map(|launch| Profile::from_launch(String::new(), launch)),
+                };
+                self.playback = Some(Playback {
+                    playlist: index,
+                    entry: 0,
+                    next_at: Instant::now(),
+                    restore,
+                });
+                self.play_entry();
+            }
+            Message::StopPlaylist => self.stop_playlist(),
+            Message::DeletePlaylist(index) => {
+                let mut playlists = self.config.playlists.clone();
+                if index < playlists.len() {
+                    playlists.remove(index);
+                    match self.playback.as_mut() {
+                        Some(playback) if playback.playlist == index => self.stop_playlist(),
+                        Some(playback) if playback.playlist > index => playback.playlist -= 1,
+                        _ => {}
+                    }
+                    self.write_config(
+                        playlists,
+                        |config| &mut config.playlists,
+                        Config::set_playlists,
+                    );
+                }
+            }
+            Message::OverrideQuietHours => {
+                self.quiet_override = true;
+                self.apply_rules();
+            }
+            Message::LockProfileSelected(index) => {
+                let profile = index
+                    .checked_sub(1)
+                    .and_then(|index| self.profile_names.get(index).cloned());
+                self.write_config(
+                    profile,
+                    |config| &mut config.lock_profile,
+                    Config::set_lock_profile,
+                );
+            }
+            Message::CleaningLengthSelected(index) => self.cleaning_length = index,
+            Message::StartCleaning => {
+                if self.set_input_blocked(true) {
+                    let seconds = CLEANING_SECONDS[self.cleaning_length];
+                    self.cleaning_until = Some(chrono::Local::now().timestamp() + seconds);
+                }
+            }
+            Message::StopCleaning => {
+                // The child lock keeps input blocked on its own.
+                if self.config.child_lock || self.set_input_blocked(false) {
+                    self.cleaning_until = None;
+                }
+            }
+            Message::StartTimer(minutes) => {
+                let due = chrono::Local::now().timestamp() + minutes * 60;
+                let index = self.timers.partition_point(|timer| *timer <= due);
+                self.timers.insert(index, due);
+            }
+            Message::CancelTimer(index) => {
+                if index < self.timers.len() {
+                    self.timers.remove(index);
+                }
+            }
+            Message::TimerNotification(enabled) => {
+                self.write_config(
+                    enabled,
+                    |config| &mut config.timer_notification,
+                    Config::set_timer_notification,
+                );
+            }
+            Message::TimerTick => {
+                let now = chrono::Local::now().timestamp();
+                // Unplugging the keyboard resets it, so there's nothing left
+                // to unblock once it's gone.
+                if self.cleaning_until.is_some_and(|until| until <= now)
+                    && (self.config.child_lock
+                        || self.launch.is_none()
+                        || self.set_input_blocked(false))
+                {
+                    self.cleaning_until = None;
+                }
+                if let Some(playback) = self.playback.as_mut() {
+                    if playback.next_at <= Instant::now() {
+                        playback.entry += 1;
+                        self.play_entry();
+                    }
+                }
+                let expired = self.timers.partition_point(|timer| *timer <= now);
+                if expired > 0 {
+                    self.timers.drain(..expired);
+                    let notification = if self.config.timer_notification {
+                        cosmic::iced::Task::future(notify::send(
+                            fl!("timer-expired"),
+                            fl!("timer-expired-body"),
+                        ))
+                        .discard()
+                    } else {
+                        Task::none()
+                    };
+                    if self.flash_restore.is_none() {
+                        if let Some(launch) = self.launch.as_ref() {
+                            self.flash_restore =
+                                Some((launch.current_mode(), launch.current_speed()));
+                            return Task::batch([notification, self.flash_step(0)]);
+                        }
+                    }
+                    return notification;
+                }
+            }
+            Message::FlashStep(step) => return self.flash_step(step),
+            Message::ToggleArranging => self.arranging = !self.arranging,
+            Message::ShowSection(section, visible) => {
+                let mut sections = self.config.arrangement.0.clone();
+                sections.retain(|s| *s != section);
+                if visible {
+                    sections.push(section);
+                }
+                self.write_config(
+                    Arrangement(sections),
+                    |config| &mut config.arrangement,
+                    Config::set_arrangement,
+                );
+            }
+            Message::MoveSection(section, offset) => {
+                let mut sections = self.config.arrangement.0.clone();
+                if let Some(index) = sections.iter().position(|s| *s == section) {
+                    let target = index.saturating_add_signed(offset as isize);
+                    if target < sections.len() {
+                        sections.swap(index, target);
+                        self.write_config(
+                            Arrangement(sections),
+                            |config| &mut config.arrangement,
+                            Config::set_arrangement,
+                        );
+                    }
+                }
+            }
+            Message::BrightnessCapSelected(index) => {
+                if let Some(cap) = BrightnessCap::CHOICES.get(index) {
+                    self.write_config(
+                        BrightnessCap(*cap),
+                        |config| &mut config.brightness_cap,
+                        Config::set_brightness_cap,
+                    );
+                    self.sync_brightness_limit();
+                }
+            }
+            Message::PopupWidthSelected(index) => {
+                if let Some(width) = PopupWidth::CHOICES.get(index) {
+                    self.write_config(
+                        PopupWidth(*width),
+                        |config| &mut config.popup_width,
+                        Config::set_popup_width,
+                    );
+                }
+            }
+            Message::CompareSecond(index) => self.compare.1 = Some(index),
+            Message::ExitProfileSelected(index) => {
+                let profile = index
+                    .checked_sub(1)
+                    .and_then(|index| self.profile_names.get(index).cloned());
+                self.write_config(
+                    profile,
+                    |config| &mut config.exit_profile,
+                    Config::set_exit_profile,
+                );
+            }
+            Message::LogoutProfileSelected(index) => {
+                let profile = index
+                    .checked_sub(1)
+                    .and_then(|index| self.profile_names.get(index).cloned());
+                self.write_config(
+                    profile,
+                    |config| &mut config.logout_profile,
+                    Config::set_logout_profile,
+                );
+            }
+            Message::MeasureLatency => {
+                self.latency_run = Some(Vec::with_capacity(LATENCY_SAMPLES));
+                return cosmic::task::message(Message::LatencyStep);
+            }
+            Message::LatencyStep => {
+                let (Some(samples), Some(launch)) =
+                    (self.latency_run.as_mut(), self.launch.as_mut())
+                else {
+                    self.latency_run = None;
+                    return Task::none();
+                };
+                for _ in 0..LATENCY_STEP {
+                    match launch.ping() {
+                        Ok(sample) => samples.push(sample),
+                        Err(err) => {
+                            eprintln!("latency measurement failed: {err}");
+                            self.latency_run = None;
+                            return Task::none();
+                        }
+                    }
+                }
+                if samples.len() < LATENCY_SAMPLES {
+                    return cosmic::task::message(Message::LatencyStep);
+                }
+                self.latency = LatencyReport::from_samples(samples);
+                self.latency_run = None;
+            }
+            Message::StressLengthSelected(index) => self.stress_length = index,
+            Message::StartStress => {
+                // The test runs on a blocking thread with a handle of its own,
+                // since a `Launch` can't leave this one.
+                let Some(launch) = self.launch.take() else {
+                    return Task::none();
+                };
+                self.metrics.retire(launch.stats());
+                self.stress_run = Some(StressReport::default());
+                let length = Duration::from_secs(STRESS_SECONDS[self.stress_length]);
+                let run = stream::channel(8, move |mut out| async move {
+                    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+                    let worker = tokio::task::spawn_blocking(move || {
+                        StressReport::run(length, |report| {
+                            let _ = tx.send(Message::StressProgress(*report));
+                        })
+                    });
+                    while let Some(progress) = rx.recv().await {
+                        let _ = out.send(progress).await;
+                    }
+                    let result = match worker.await {
+                        Ok(result) => result.map_err(|err| err.to_string()),
+                        Err(err) => Err(err.to_string()),
+                    };
+                    let _ = out.send(Message::StressFinished(result)).await;
+                });
+                return cosmic::iced::Task::run(run, cosmic::Action::App);
+            }
+            Message::StressProgress(report) => {
+                if self.stress_run.is_some() {
+                    self.stress_run = Some(report);
+                }
+            }
+            Message::StressFinished(result) => {
+                self.stress_run = None;
+                match result {
+                    Ok(report) => {
+                        eprintln!("EC stress test finished: {report:?}");
+                        self.stress = Some(report);
+                    }
+                    Err(err) => self.log_event(format!("EC stress test failed: {err}")),
+                }
+                self.open_launch();
+                self.refresh_snapshot();
+                self.apply_pending();
+            }
+            Message::SpeedPreview(speed) => {
+                self.speed_preview = Some(speed);
+                if !self.config.staged_editing && self.preview_throttle.ready() {
+                    self.write_speed(speed);
+                }
+            }
+            Message::SpeedCommit => {
+                self.preview_throttle.reset();
+                if let Some(speed) = self.speed_preview.take() {
+                    if !self.stage(|staged| staged.speed = speed) && self.write_speed(speed) {
+                        self.bus.publish(Event::SpeedChanged(speed));
+                    }
+                }
+            }
+            Message::SpeedInput(text) => self.speed_input = Some(text),
+            Message::SetSpeed(speed) => {
+                self.speed_input = None;
+                self.sync_external_changes();
+                if !self.stage(|staged| staged.speed = speed) && self.write_speed(speed) {
+                    self.bus.publish(Event::SpeedChanged(speed));
+                }
+            }
+            Message::BrightnessPreview(brightness) => {
+                self.brightness_preview = Some(brightness);
+                if !self.config.staged_editing && self.preview_throttle.ready() {
+                    self.write_brightness(brightness);
+                }
+            }
+            Message::BrightnessCommit => {
+                self.preview_throttle.reset();
+                if let Some(brightness) = self.brightness_preview.take() {
+                    if !self.stage(|staged| staged.brightness = Some(brightness))
+                        && self.write_brightness(brightness)
+                    {
+                        self.bus.publish(Event::BrightnessChanged(brightness));
+                    }
+                }
+            }
+            Message::BrightnessInput(text) => self.brightness_input = Some(text),
+            Message::SetBrightness(brightness) => {
+                self.brightness_input = None;
+                self.sync_external_changes();
+                if !self.stage(|staged| staged.brightness = Some(brightness))
+                    && self.write_brightness(brightness)
+                {
+                    self.bus.publish(Event::BrightnessChanged(brightness));
+                }
+            }
+            Message::ColorPreview(color) => {
+                self.color_preview = Some(color);
+                if !self.config.staged_editing && self.preview_throttle.ready() {
+                    self.write_color(color);
+                }
+            }
+            Message::ColorCommit => {
+                self.preview_throttle.reset();
+                if let Some(color) = self.color_preview.take() {
+                    if !self.stage(|staged| staged.color = Some(color)) && self.write_color(color) {
+                        self.bus.publish(Event::ColorChanged(color));
+                    }
+                }
+            }
+            Message::SliderStepSelected(index) => {
+                if let Some(step) = SliderStep::CHOICES.get(index) {
+                    self.write_config(
+                        SliderStep(*step),
+                        |config| &mut config.slider_step,
+                        Config::set_slider_step,
+                    );
+                }
+            }
+            Message::DndChanged(dnd) => {
+                self.dnd = dnd;
+                self.apply_rules();
+            }
+            Message::ToggleDnd(dnd) => {
+                if let Some(handler) = self.notifications_handler.as_ref() {
+                    // The setter only writes when the value changes, so start
+                    // from what the daemon last reported.
+                    let mut notifications = NotificationsConfig {
+                        do_not_disturb: self.dnd,
+                    };
+                    if let Err(err) = notifications.set_do_not_disturb(handler, dnd) {
+                        eprintln!("failed to toggle do not disturb: {err}");
+                    }
+                }
+            }
+            Message::CheckConflicts => {
+                return cosmic::task::future(async {
+                    Message::ConflictsChecked(conflicts::running().await)
+                });
+            }
+            Message::ConflictsChecked(running) => {
+                let detected: Vec<_> = running
+                    .iter()
+                    .filter(|controller| !self.external.contains(controller))
+                    .map(|controller| {
+                        cosmic::task::message(Message::ExternalControllerDetected(*controller))
+                    })
+                    .collect();
+                self.external = running;
+                self.update_yield();
+                return Task::batch(detected);
+            }
+            Message::PollState => self.sync_external_changes(),
+            Message::ExternalControllerDetected(controller) => {
+                self.log_event(format!("{} started", controller.name()));
+            }
+            Message::YieldTo(controller, enabled) => {
+                let mut yield_to = self.config.yield_to.0.clone();
+                yield_to.retain(|c| *c != controller);
+                if enabled {
+                    yield_to.push(controller);
+                }
+                self.write_config(
+                    YieldTo(yield_to),
+                    |config| &mut config.yield_to,
+                    Config::set_yield_to,
+                );
+                self.update_yield();
+            }
+            Message::ExplainRules => {
+                let context = self.current_context();
+                self.explanation = Some(rules::evaluate(&self.config.rules, &context).explain());
+            }
+            Message::SimulatedApp(app_id) => {
+                self.simulation.focused_app = (!app_id.is_empty()).then_some(app_id);
+            }
+            Message::SimulatedTime(time) => self.simulation_time = time,
+            Message::SimulatedWeekend(weekend) => self.simulation.weekend = weekend,
+            Message::SimulatedBattery(on_battery) => self.simulation.on_battery = on_battery,
+            Message::SimulatedLocked(locked) => self.simulation.locked = locked,
+            Message::SimulatedDnd(dnd) => self.simulation.do_not_disturb = dnd,
+            Message::Simulate => {
+                let now = self.current_context();
+                let context = Context {
+                    device_connected: true,
+                    minutes: rules::parse_time(&self.simulation_time).unwrap_or(now.minutes),
+                    timestamp: now.timestamp,
+                    indicators: now.indicators,
+                    ..self.simulation.clone()
+                };
+                self.explanation = Some(rules::evaluate(&self.config.rules, &context).explain());
+            }
         }
         Task::none()
     }
@@ -145,3 +3592,61 @@ impl Application for LaunchControl {
         Some(cosmic::applet::style())
     }
 }
+
+/// Forwards every event on the bus into the iced runtime.
+fn bus_events(bus: &EventBus) -> Subscription<Event> {
+    let mut rx = bus.subscribe();
+    Subscription::run_with_id(
+        "event-bus",
+        stream::channel(128, |mut out| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        let _ = out.send(event).await;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        eprintln!("event bus subscriber lagged, skipped {skipped} events");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }),
+    )
+}
+
+fn describe_change(change: &Change) -> String {
+    match change {
+        Change::Mode { from, to } => {
+            fl!("change-mode", from = from.to_string(), to = to.to_string())
+        }
+        Change::Speed { from, to } => fl!("change-speed", from = *from, to = *to),
+        Change::Brightness { from, to } => fl!("change-brightness", from = *from, to = *to),
+        Change::Color { from, to } => {
+            fl!("change-color", from = from.to_string(), to = to.to_string())
+        }
+        change => format!("{change:?}"),
+    }
+}
+
+fn describe_health(health: LinkHealth) -> String {
+    match health {
+        LinkHealth::Healthy => fl!("link-healthy"),
+        LinkHealth::Slow => fl!("link-slow"),
+        LinkHealth::Degraded { errors } => fl!("link-degraded", errors = errors),
+        health => format!("{health:?}"),
+    }
+}
+
+/// Formats a Unix timestamp as a local time of day.
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| {
+            let time = time.with_timezone(&chrono::Local);
+            format::time((time.hour() * 60 + time.minute()) as u16)
+        })
+        .unwrap_or_default()
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{} ms", format::decimal(duration.as_secs_f64() * 1000.0, 2))
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use tokio::sync::broadcast;
+
+use crate::device_listener::DeviceInfo;
+use crate::launch::{Color, LedMode};
+use crate::shutdown::ExitReason;
+
+/// Something that happened in one subsystem that others may care about.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Event {
+    DeviceConnected(DeviceInfo),
+    /// A USB device was unplugged, any device and not only a Launch.
+    DeviceDisconnected(DeviceInfo),
+    /// The lighting mode was changed from the popup.
+    ModeChanged(LedMode),
+    /// The animation speed was changed from the popup.
+    SpeedChanged(u8),
+    /// The brightness was changed from the popup.
+    BrightnessChanged(u8),
+    /// The color was changed from the popup.
+    ColorChanged(Color),
+    /// A profile was applied, by the named rule if `rule` is set.
+    ProfileApplied {
+        profile: String,
+        rule: Option<String>,
+    },
+    /// The kernel logged a message about a USB device.
+    KernelMessage(String),
+    /// The applet was asked to exit.
+    ShutdownRequested(ExitReason),
+    /// A background task failed and is being restarted.
+    TaskRestarted {
+        task: &'static str,
+        error: String,
+    },
+}
+
+/// Broadcast channel connecting the applet's subsystems.
+///
+/// Every subscriber sees every event, including the ones it published itself.
+/// Cloning the bus is cheap and yields a handle to the same channel.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(128);
+        Self { tx }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, event: Event) {
+        // Sending only fails when nobody is subscribed, which is fine.
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+}
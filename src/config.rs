@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::BTreeMap;
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+use crate::conflicts::ExternalController;
+use crate::fl;
+use crate::format;
+use crate::launch::LedMode;
+use crate::playlist::Playlist;
+use crate::profile::Profile;
+use crate::rules::{self, Rule};
+use crate::scene::Scene;
+
+/// How the mode and profile lists are ordered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModeOrder {
+    /// The order the firmware defines the modes in.
+    #[default]
+    Default,
+    /// Most frequently applied modes and profiles first, falling back to the
+    /// default order.
+    MostUsed,
+}
+
+/// How far the nudge buttons next to a slider move it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SliderStep(pub u8);
+
+impl SliderStep {
+    /// Steps offered in the popup.
+    pub const CHOICES: [u8; 4] = [1, 5, 10, 25];
+}
+
+impl Default for SliderStep {
+    fn default() -> Self {
+        Self(5)
+    }
+}
+
+/// A block of controls on the modes tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Section {
+    DoNotDisturb,
+    Search,
+    ModeOrder,
+    Speed,
+    Brightness,
+    Color,
+    SliderStep,
+    Editing,
+    Modes,
+    Timers,
+}
+
+impl Section {
+    pub const ALL: [Section; 10] = [
+        Section::DoNotDisturb,
+        Section::Search,
+        Section::ModeOrder,
+        Section::Speed,
+        Section::Brightness,
+        Section::Color,
+        Section::SliderStep,
+        Section::Editing,
+        Section::Modes,
+        Section::Timers,
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            Self::DoNotDisturb => fl!("do-not-disturb"),
+            Self::Search => fl!("section-search"),
+            Self::ModeOrder => fl!("most-used-first"),
+            Self::Speed => fl!("speed"),
+            Self::Brightness => fl!("brightness"),
+            Self::Color => fl!("color"),
+            Self::SliderStep => fl!("slider-step"),
+            Self::Editing => fl!("staged-editing"),
+            Self::Modes => fl!("section-modes"),
+            Self::Timers => fl!("timers"),
+        }
+    }
+}
+
+/// Which sections the modes tab shows, in display order. Sections that
+/// aren't listed are hidden.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arrangement(pub Vec<Section>);
+
+impl Default for Arrangement {
+    fn default() -> Self {
+        Self(Section::ALL.to_vec())
+    }
+}
+
+/// Maximum width of the popup in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PopupWidth(pub u16);
+
+impl PopupWidth {
+    /// Widths offered in the popup.
+    pub const CHOICES: [u16; 3] = [372, 480, 600];
+}
+
+impl Default for PopupWidth {
+    fn default() -> Self {
+        Self(372)
+    }
+}
+
+/// Highest brightness the applet sets, in percent of what the firmware
+/// accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrightnessCap(pub u8);
+
+impl BrightnessCap {
+    /// Caps offered in the device tab.
+    pub const CHOICES: [u8; 5] = [100, 80, 60, 40, 20];
+
+    /// The cap as a brightness value for a keyboard whose maximum is `max`.
+    pub fn limit(self, max: u8) -> u8 {
+        (u16::from(max) * u16::from(self.0.min(100)) / 100) as u8
+    }
+}
+
+impl Default for BrightnessCap {
+    fn default() -> Self {
+        Self(100)
+    }
+}
+
+/// A daily window, in minutes since midnight, during which the lighting is
+/// kept off. Windows where `end` is before `start` wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl QuietHours {
+    /// Parses `HH:MM-HH:MM`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (start, end) = text.split_once('-')?;
+        Some(Self {
+            start: rules::parse_time(start)?,
+            end: rules::parse_time(end)?,
+        })
+    }
+
+    pub fn contains(self, minutes: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minutes)
+        } else {
+            minutes >= self.start || minutes < self.end
+        }
+    }
+
+    /// Writes the range on the locale's clock, in a form [`QuietHours::parse`]
+    /// reads back.
+    pub fn describe(self) -> String {
+        format!("{}-{}", format::time(self.start), format::time(self.end))
+    }
+}
+
+/// Other programs the applet leaves the keyboard to while they run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YieldTo(pub Vec<ExternalController>);
+
+impl Default for YieldTo {
+    fn default() -> Self {
+        Self(vec![ExternalController::Configurator])
+    }
+}
+
+/// A tab of the popup.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Tab {
+    #[default]
+    Modes,
+    Profiles,
+    Rules,
+    Device,
+    Diagnostics,
+}
+
+#[derive(Debug, Default, Clone, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct Config {
+    /// How often each mode has been applied from the popup.
+    pub mode_usage: BTreeMap<LedMode, u32>,
+    pub mode_order: ModeOrder,
+    pub profiles: Vec<Profile>,
+    /// How often each profile, by name, has been applied from the popup.
+    pub profile_usage: BTreeMap<String, u32>,
+    /// Auto-apply rules, highest priority first.
+    pub rules: Vec<Rule>,
+    pub scenes: Vec<Scene>,
+    pub playlists: Vec<Playlist>,
+    /// Profile applied when the applet exits while the session goes on, like
+    /// a panel restart. `None` leaves the keyboard as it is.
+    pub exit_profile: Option<String>,
+    /// Profile applied at logout, shutdown and reboot. `None` leaves the
+    /// keyboard as it is.
+    pub logout_profile: Option<String>,
+    pub slider_step: SliderStep,
+    pub arrangement: Arrangement,
+    pub popup_width: PopupWidth,
+    /// Whether mode and speed changes wait for an explicit apply instead of
+    /// going to the keyboard straight away.
+    pub staged_editing: bool,
+    /// Lighting last set through the applet, written after every committed
+    /// change so it can be restored after a crash or power loss.
+    pub journal: Option<Profile>,
+    pub yield_to: YieldTo,
+    /// When the lighting is forced off, whatever rules or the popup ask for.
+    pub quiet_hours: Option<QuietHours>,
+    /// Limit every brightness write is held to, by sliders and automations
+    /// alike.
+    pub brightness_cap: BrightnessCap,
+    /// Whether key input stays blocked until unlocked from the applet.
+    pub child_lock: bool,
+    /// Profile applied while the child lock is on, so it's obvious why typing
+    /// does nothing. `None` leaves the lighting as it is.
+    pub lock_profile: Option<String>,
+    /// Boards whose lighting was already saved as a "Device default"
+    /// profile.
+    pub known_boards: Vec<String>,
+    /// Tab the popup was last showing, reopened on the next start.
+    pub last_tab: Tab,
+    /// Whether an expired timer also shows a desktop notification.
+    pub timer_notification: bool,
+}
+
+impl Config {
+    /// Number of times `mode` has been applied.
+    pub fn usage(&self, mode: LedMode) -> u32 {
+        self.mode_usage.get(&mode).copied().unwrap_or_default()
+    }
+
+    /// Number of times the profile named `name` has been applied.
+    pub fn profile_usage(&self, name: &str) -> u32 {
+        self.profile_usage.get(name).copied().unwrap_or_default()
+    }
+}
+
+/// Config id of COSMIC's notification daemon.
+pub const NOTIFICATIONS_ID: &str = "com.system76.CosmicNotifications";
+
+/// The part of COSMIC's notification settings the applet follows.
+#[derive(Debug, Default, Clone, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct NotificationsConfig {
+    pub do_not_disturb: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hours_wrap_around_midnight() {
+        let night = QuietHours::parse("22:00-07:00").unwrap();
+        assert!(night.contains(rules::parse_time("22:00").unwrap()));
+        assert!(night.contains(rules::parse_time("03:00").unwrap()));
+        assert!(!night.contains(rules::parse_time("07:00").unwrap()));
+        assert!(!night.contains(rules::parse_time("21:59").unwrap()));
+
+        let day = QuietHours::parse("09:00-17:00").unwrap();
+        assert!(day.contains(rules::parse_time("12:00").unwrap()));
+        assert!(!day.contains(rules::parse_time("17:00").unwrap()));
+        assert!(!day.contains(rules::parse_time("03:00").unwrap()));
+    }
+
+    #[test]
+    fn parses_quiet_hours() {
+        let night = QuietHours {
+            start: 22 * 60,
+            end: 7 * 60,
+        };
+        assert_eq!(QuietHours::parse("22:00-07:00"), Some(night));
+        assert_eq!(QuietHours::parse("10:00 PM-7:00 AM"), Some(night));
+        assert_eq!(QuietHours::parse("22:00"), None);
+        assert_eq!(QuietHours::parse("22:00-25:00"), None);
+    }
+}
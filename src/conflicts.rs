@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Another program that drives Launch keyboards itself and would overwrite
+/// the applet's changes, or have its own overwritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum ExternalController {
+    /// System76 Keyboard Configurator.
+    Configurator,
+    OpenRgb,
+}
+
+impl ExternalController {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Configurator => "Keyboard Configurator",
+            Self::OpenRgb => "OpenRGB",
+        }
+    }
+
+    fn executable(self) -> &'static str {
+        match self {
+            Self::Configurator => "system76-keyboard-configurator",
+            Self::OpenRgb => "openrgb",
+        }
+    }
+}
+
+/// The external controllers running right now, judged by the processes we
+/// can inspect. Walks `/proc` on a blocking thread.
+pub async fn running() -> Vec<ExternalController> {
+    tokio::task::spawn_blocking(scan).await.unwrap_or_default()
+}
+
+fn scan() -> Vec<ExternalController> {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut running = Vec::new();
+    for process in processes.flatten() {
+        let Ok(cmdline) = fs::read(process.path().join("cmdline")) else {
+            continue;
+        };
+        let program = cmdline.split(|byte| *byte == 0).next().unwrap_or_default();
+        let program = String::from_utf8_lossy(program);
+        let Some(file) = Path::new(&*program).file_name() else {
+            continue;
+        };
+        for controller in ExternalController::iter() {
+            if file.to_str() == Some(controller.executable()) && !running.contains(&controller) {
+                running.push(controller);
+            }
+        }
+    }
+    running
+}
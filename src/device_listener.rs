@@ -2,14 +2,37 @@ use std::{
     collections::HashMap,
     time::{Duration, Instant},
 };
-use tokio_udev::{Enumerator, MonitorBuilder};
-use futures::StreamExt;
+
+use futures_util::StreamExt;
+use tokio_udev::{AsyncMonitorSocket, Enumerator, EventType, MonitorBuilder};
+
+use crate::bus::{Event, EventBus};
+
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct DeviceInfo {
+    pub vid: u32,
+    pub pid: u32,
+}
+
+impl DeviceInfo {
+    /// Whether the device belongs to the Launch family.
+    pub fn is_launch(&self) -> bool {
+        self.vid == 0x3384 && (0x0001..=0x000A).contains(&self.pid)
+    }
+}
 
 pub struct DeviceListener {
     subsystem: &'static str,
     debounce: Duration,
 }
 
+impl Default for DeviceListener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DeviceListener {
     pub fn new() -> Self {
         Self {
@@ -18,22 +41,23 @@ impl DeviceListener {
         }
     }
 
-    // Start listening, send Messages into the given sender.
-    pub async fn run(self, mut out: iced::subscription::Channel<Message>) {
+    // Start listening, publish device events on the given bus.
+    pub async fn run(self, bus: EventBus) {
         let mut last_event: HashMap<String, Instant> = HashMap::new();
 
         // Enumerate existing devices
-        if let Ok(mut enumr) = Enumerator::new() {
-            if enumr.match_subsystem(self.subsystem).is_ok() {
-                if let Ok(devs) = enumr.scan_devices() {
-                    for dev in devs {
-                        if let Some(info) = extract_info(&dev) {
-                            if should_fire(&mut last_event, &info, self.debounce) {
-                                let _ = out.send(Message::DeviceConnected(info)).await;
-                            }
-                        }
-                    }
-                }
+        let existing: Vec<DeviceInfo> = Enumerator::new()
+            .and_then(|mut enumr| {
+                enumr.match_subsystem(self.subsystem)?;
+                Ok(enumr
+                    .scan_devices()?
+                    .filter_map(|dev| extract_info(&dev))
+                    .collect())
+            })
+            .unwrap_or_default();
+        for info in existing {
+            if should_fire(&mut last_event, &info, self.debounce) {
+                bus.publish(Event::DeviceConnected(info));
             }
         }
 
@@ -41,6 +65,7 @@ impl DeviceListener {
         let monitor = match MonitorBuilder::new()
             .and_then(|m| m.match_subsystem(self.subsystem))
             .and_then(|m| m.listen())
+            .and_then(AsyncMonitorSocket::new)
         {
             Ok(m) => m,
             Err(err) => {
@@ -51,17 +76,17 @@ impl DeviceListener {
         tokio::pin!(monitor);
 
         while let Some(evt) = monitor.next().await {
-            if let Some(info) = extract_info(&evt.device()) {
+            let (event_type, info) = match evt {
+                Ok(evt) => (evt.event_type(), extract_info(&evt.device())),
+                Err(_) => continue,
+            };
+            if let Some(info) = info {
                 if !should_fire(&mut last_event, &info, self.debounce) {
                     continue;
                 }
-                match evt.event_type().as_str() {
-                    "add" => {
-                        let _ = out.send(Message::DeviceConnected(info)).await;
-                    }
-                    "remove" => {
-                        let _ = out.send(Message::DeviceDisconnected).await;
-                    }
+                match event_type {
+                    EventType::Add => bus.publish(Event::DeviceConnected(info)),
+                    EventType::Remove => bus.publish(Event::DeviceDisconnected(info)),
                     _ => {}
                 }
             }
@@ -85,11 +110,7 @@ fn extract_info(dev: &tokio_udev::Device) -> Option<DeviceInfo> {
     })
 }
 
-fn should_fire(
-    last: &mut HashMap<String, Instant>,
-    info: &DeviceInfo,
-    win: Duration,
-) -> bool {
+fn should_fire(last: &mut HashMap<String, Instant>, info: &DeviceInfo, win: Duration) -> bool {
     let key = format!("{:04x}:{:04x}", info.vid, info.pid);
     let now = Instant::now();
     match last.get(&key) {
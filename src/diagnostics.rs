@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::launch::{EcStats, Launch, LaunchError};
+
+/// How often a running stress test reports its tally.
+const STRESS_PROGRESS: Duration = Duration::from_millis(250);
+
+/// Latency statistics over a series of EC round trips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyReport {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyReport {
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        Some(Self {
+            samples: sorted.len(),
+            p50: percentile(&sorted, 50),
+            p95: percentile(&sorted, 95),
+            p99: percentile(&sorted, 99),
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Nearest-rank percentile of already sorted samples.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Outcome of a stress test of the EC link.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StressReport {
+    pub cycles: u32,
+    pub retries: u32,
+    pub errors: u32,
+}
+
+impl StressReport {
+    /// Runs one read/write cycle, retrying it once before counting an error.
+    pub fn record<E>(&mut self, mut cycle: impl FnMut() -> Result<(), E>) {
+        self.cycles += 1;
+        if cycle().is_ok() {
+            return;
+        }
+        self.retries += 1;
+        if cycle().is_err() {
+            self.errors += 1;
+        }
+    }
+
+    /// Hammers the EC link with read/write cycles for `length` through a
+    /// handle of its own, passing the tally so far to `progress` now and then.
+    /// Blocks, so it belongs on a thread of its own.
+    pub fn run(length: Duration, mut progress: impl FnMut(&Self)) -> Result<Self, LaunchError> {
+        let mut launch = Launch::try_new()?;
+        let mut report = Self::default();
+        let deadline = Instant::now() + length;
+        let mut next_progress = Instant::now() + STRESS_PROGRESS;
+        while Instant::now() < deadline {
+            report.record(|| launch.echo_mode());
+            if Instant::now() >= next_progress {
+                progress(&report);
+                next_progress += STRESS_PROGRESS;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Share of cycles that failed even after a retry.
+    pub fn error_rate(&self) -> f64 {
+        if self.cycles == 0 {
+            return 0.0;
+        }
+        f64::from(self.errors) / f64::from(self.cycles)
+    }
+}
+
+/// Counters describing the applet's own health, across device reconnects.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    started: Instant,
+    /// Traffic of device handles that have since been closed.
+    retired: EcStats,
+    /// Times the keyboard came back after being gone.
+    pub reconnects: u32,
+    /// Whether a device handle was ever opened.
+    opened: bool,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            retired: EcStats::default(),
+            reconnects: 0,
+            opened: false,
+        }
+    }
+}
+
+impl Metrics {
+    /// Keeps the counters of a handle that is being closed.
+    pub fn retire(&mut self, stats: EcStats) {
+        self.retired = self.retired + stats;
+    }
+
+    /// Records that a handle was opened while none was open.
+    pub fn opened(&mut self) {
+        if self.opened {
+            self.reconnects += 1;
+        }
+        self.opened = true;
+    }
+
+    /// Totals including the currently open handle, if any.
+    pub fn ec(&self, current: Option<EcStats>) -> EcStats {
+        self.retired + current.unwrap_or_default()
+    }
+
+    /// Average EC commands per second since the applet started.
+    pub fn commands_per_second(&self, total: EcStats) -> f64 {
+        total.commands as f64 / self.started.elapsed().as_secs_f64().max(1.0)
+    }
+
+    /// The counters in the Prometheus text exposition format.
+    pub fn to_prometheus(&self, total: EcStats) -> String {
+        let mut text = String::new();
+        let counters = [
+            (
+                "launch_ec_commands_total",
+                "EC commands sent.",
+                total.commands,
+            ),
+            (
+                "launch_ec_errors_total",
+                "EC calls that failed.",
+                total.errors,
+            ),
+            (
+                "launch_reconnects_total",
+                "Times the keyboard reconnected.",
+                u64::from(self.reconnects),
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(text, "# HELP {name} {help}");
+            let _ = writeln!(text, "# TYPE {name} counter");
+            let _ = writeln!(text, "{name} {value}");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn no_samples_no_report() {
+        assert_eq!(LatencyReport::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn one_sample_is_every_percentile() {
+        let report = LatencyReport::from_samples(&[ms(7)]).unwrap();
+        assert_eq!(report.samples, 1);
+        assert_eq!([report.p50, report.p95, report.p99, report.max], [ms(7); 4]);
+    }
+
+    #[test]
+    fn hundred_samples() {
+        // Reversed, so the report has to sort them itself.
+        let samples: Vec<_> = (1..=100).rev().map(ms).collect();
+        let report = LatencyReport::from_samples(&samples).unwrap();
+        assert_eq!(report.samples, 100);
+        assert_eq!(report.p50, ms(50));
+        assert_eq!(report.p95, ms(95));
+        assert_eq!(report.p99, ms(99));
+        assert_eq!(report.max, ms(100));
+    }
+
+    #[test]
+    fn nearest_rank_rounds_up() {
+        let samples: Vec<_> = (1..=10).map(ms).collect();
+        let report = LatencyReport::from_samples(&samples).unwrap();
+        assert_eq!(report.p50, ms(5));
+        assert_eq!(report.p95, ms(10));
+        assert_eq!(report.p99, ms(10));
+        assert_eq!(percentile(&samples, 0), ms(1));
+        assert_eq!(percentile(&samples, 100), ms(10));
+    }
+
+    #[test]
+    fn stress_counts_retries_and_errors() {
+        let mut report = StressReport::default();
+        report.record(|| Ok::<(), ()>(()));
+        let mut attempts = 0;
+        report.record(|| {
+            attempts += 1;
+            if attempts == 1 {
+                Err(())
+            } else {
+                Ok(())
+            }
+        });
+        report.record(|| Err::<(), ()>(()));
+        report.record(|| Ok::<(), ()>(()));
+        assert_eq!(
+            report,
+            StressReport {
+                cycles: 4,
+                retries: 2,
+                errors: 1,
+            }
+        );
+        assert_eq!(report.error_rate(), 0.25);
+    }
+
+    #[test]
+    fn stress_error_rate_without_cycles() {
+        assert_eq!(StressReport::default().error_rate(), 0.0);
+        let all_failed = StressReport {
+            cycles: 3,
+            retries: 3,
+            errors: 3,
+        };
+        assert_eq!(all_failed.error_rate(), 1.0);
+    }
+}
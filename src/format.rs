@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Number and time formatting that follows the user's locale settings
+//! rather than always looking English.
+
+use std::env;
+
+/// Languages written with a decimal comma.
+const DECIMAL_COMMA: &[&str] = &[
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "lt", "lv",
+    "nb", "nl", "nn", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr", "uk",
+];
+
+/// Regions that use a 12-hour clock.
+const TWELVE_HOUR: &[&str] = &["AU", "CA", "IN", "NZ", "PH", "US"];
+
+/// Language and region of a locale category, e.g. `("nl", "NL")` for
+/// `nl_NL.UTF-8`. `LC_ALL` overrides the category, which overrides `LANG`.
+fn locale(category: &str) -> (String, String) {
+    locale_from(category, |name| env::var(name).ok())
+}
+
+/// [`locale`] with the environment looked up through `var`.
+fn locale_from(category: &str, var: impl Fn(&str) -> Option<String>) -> (String, String) {
+    let value = ["LC_ALL", category, "LANG"]
+        .into_iter()
+        .filter_map(var)
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let name = value.split(['.', '@']).next().unwrap_or_default();
+    let (language, region) = name.split_once('_').unwrap_or((name, ""));
+    (language.to_lowercase(), region.to_uppercase())
+}
+
+/// Formats `value` with `digits` decimals and the locale's decimal mark.
+pub fn decimal(value: f64, digits: usize) -> String {
+    decimal_in(&locale("LC_NUMERIC").0, value, digits)
+}
+
+fn decimal_in(language: &str, value: f64, digits: usize) -> String {
+    let text = format!("{value:.digits$}");
+    if DECIMAL_COMMA.contains(&language) {
+        text.replace('.', ",")
+    } else {
+        text
+    }
+}
+
+/// Formats a time of day, given in minutes since midnight, on the locale's
+/// clock.
+pub fn time(minutes: u16) -> String {
+    time_in(&locale("LC_TIME").1, minutes)
+}
+
+fn time_in(region: &str, minutes: u16) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    if !TWELVE_HOUR.contains(&region) {
+        return format!("{hours:02}:{minutes:02}");
+    }
+    let suffix = if hours < 12 { "AM" } else { "PM" };
+    let hours = match hours % 12 {
+        0 => 12,
+        hours => hours,
+    };
+    format!("{hours}:{minutes:02} {suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn locale_with(category: &str, vars: &[(&str, &str)]) -> (String, String) {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        locale_from(category, |name| {
+            vars.get(name).map(|value| value.to_string())
+        })
+    }
+
+    #[test]
+    fn reads_the_locale_in_order() {
+        let full = [
+            ("LC_ALL", "nl_NL.UTF-8"),
+            ("LC_TIME", "en_US.UTF-8"),
+            ("LANG", "de_DE.UTF-8"),
+        ];
+        assert_eq!(locale_with("LC_TIME", &full), ("nl".into(), "NL".into()));
+        assert_eq!(
+            locale_with("LC_TIME", &full[1..]),
+            ("en".into(), "US".into())
+        );
+        assert_eq!(
+            locale_with("LC_NUMERIC", &full[1..]),
+            ("de".into(), "DE".into())
+        );
+        assert_eq!(
+            locale_with("LC_TIME", &[("LC_ALL", ""), ("LANG", "en_GB@euro")]),
+            ("en".into(), "GB".into())
+        );
+        assert_eq!(
+            locale_with("LC_TIME", &[("LANG", "C")]),
+            ("c".into(), "".into())
+        );
+        assert_eq!(locale_with("LC_TIME", &[]), ("".into(), "".into()));
+    }
+
+    #[test]
+    fn decimal_mark_follows_the_language() {
+        assert_eq!(decimal_in("nl", 1.26, 1), "1,3");
+        assert_eq!(decimal_in("de", 3.0, 2), "3,00");
+        assert_eq!(decimal_in("en", 1.25, 2), "1.25");
+        assert_eq!(decimal_in("", 0.5, 1), "0.5");
+    }
+
+    #[test]
+    fn clock_follows_the_region() {
+        assert_eq!(time_in("NL", 0), "00:00");
+        assert_eq!(time_in("NL", 13 * 60 + 5), "13:05");
+        assert_eq!(time_in("GB", 23 * 60 + 59), "23:59");
+        assert_eq!(time_in("US", 0), "12:00 AM");
+        assert_eq!(time_in("US", 9 * 60 + 30), "9:30 AM");
+        assert_eq!(time_in("US", 12 * 60), "12:00 PM");
+        assert_eq!(time_in("US", 23 * 60 + 59), "11:59 PM");
+    }
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use cosmic::iced::futures::future;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// How long a script may run before its reading is dropped.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Something that reports a small number rules can react to, like the count
+/// of unread mails. Zero means "nothing to indicate".
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IndicatorSource {
+    /// A shell command that prints a number, `true` or `false`.
+    Script(String),
+}
+
+impl IndicatorSource {
+    pub async fn read(&self) -> Option<u32> {
+        match self {
+            Self::Script(command) => read_script(command).await,
+        }
+    }
+}
+
+/// Reads all `sources` concurrently, leaving out the ones that failed.
+pub async fn read_all(sources: Vec<IndicatorSource>) -> HashMap<IndicatorSource, u32> {
+    let readings = future::join_all(sources.iter().map(IndicatorSource::read)).await;
+    sources
+        .into_iter()
+        .zip(readings)
+        .filter_map(|(source, reading)| Some((source, reading?)))
+        .collect()
+}
+
+async fn read_script(command: &str) -> Option<u32> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .kill_on_drop(true)
+        .output();
+    let output = match tokio::time::timeout(SCRIPT_TIMEOUT, output).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(err)) => {
+            eprintln!("failed to run indicator script {command:?}: {err}");
+            return None;
+        }
+        Err(_) => {
+            eprintln!("indicator script {command:?} timed out");
+            return None;
+        }
+    };
+    parse_value(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_value(text: &str) -> Option<u32> {
+    match text.trim() {
+        "true" => Some(1),
+        "false" => Some(0),
+        number => number.parse().ok(),
+    }
+}
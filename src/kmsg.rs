@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::io::{ErrorKind, SeekFrom};
+
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+use crate::bus::{Event, EventBus};
+
+/// Follows the kernel log and publishes USB messages on the bus, so resets and
+/// disconnects reported by the host show up next to the applet's own errors.
+///
+/// Reading `/dev/kmsg` is refused when `kernel.dmesg_restrict` is set, in which
+/// case this logs once and returns.
+pub async fn run(bus: EventBus) {
+    let mut kmsg = match File::open("/dev/kmsg").await {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("can't follow the kernel log: {err}");
+            return;
+        }
+    };
+    // Only messages logged from now on are of interest.
+    if let Err(err) = kmsg.seek(SeekFrom::End(0)).await {
+        eprintln!("can't follow the kernel log: {err}");
+        return;
+    }
+
+    // Every read returns exactly one record.
+    let mut record = vec![0; 8192];
+    loop {
+        let len = match kmsg.read(&mut record).await {
+            Ok(0) => return,
+            Ok(len) => len,
+            // The reader fell behind and records were overwritten.
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => continue,
+            Err(err) => {
+                eprintln!("kernel log read error: {err}");
+                return;
+            }
+        };
+        if let Some(message) = usb_message(&String::from_utf8_lossy(&record[..len])) {
+            bus.publish(Event::KernelMessage(message.to_string()));
+        }
+    }
+}
+
+/// Extracts the text of a `/dev/kmsg` record if it's about a USB device.
+///
+/// Records look like `6,1234,5678,-;usb 1-2.3: reset full-speed USB device`,
+/// optionally followed by continuation lines.
+fn usb_message(record: &str) -> Option<&str> {
+    let (_, text) = record.split_once(';')?;
+    let message = text.lines().next()?;
+    message.starts_with("usb ").then_some(message)
+}
@@ -1,12 +1,22 @@
-use std::{error, fmt, string::FromUtf8Error};
+use std::{
+    collections::VecDeque,
+    error, fmt,
+    string::FromUtf8Error,
+    time::{Duration, Instant},
+};
 
 use ectool::{Access, AccessHid, Ec, Error as EcError};
 use hidapi::{HidApi, HidError};
+use serde::{Deserialize, Serialize};
 use strum::{EnumCount, EnumIter};
 use thiserror::Error;
 
+use crate::profile::Profile;
+use crate::usb::UsbTopology;
+
+/// An error reported by the EC. Only its message is part of the API.
 #[derive(Debug)]
-pub struct EcWrap(pub EcError);
+pub struct EcWrap(EcError);
 
 impl fmt::Display for EcWrap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -16,6 +26,7 @@ impl fmt::Display for EcWrap {
 impl error::Error for EcWrap {}
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum LaunchError {
     #[error("EC error: {0}")]
     Ec(#[from] EcWrap),
@@ -35,8 +46,11 @@ impl From<EcError> for LaunchError {
     }
 }
 
-#[derive(EnumIter, EnumCount, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(
+    EnumIter, EnumCount, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 #[repr(u8)]
+#[non_exhaustive]
 pub enum LedMode {
     SolidColor = 0,
     PerKey,
@@ -105,12 +119,257 @@ impl fmt::Display for LedMode {
     }
 }
 
+/// What a [`LedMode`] does with the lighting parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModeInfo {
+    /// Whether the lighting moves on its own.
+    pub animated: bool,
+    pub uses_speed: bool,
+    /// Speed bytes for slow, medium and fast. The same byte feels very
+    /// different between modes, so each kind of mode has its own. All zero
+    /// for modes that don't use the speed.
+    pub speed_presets: [u8; 3],
+    /// Whether the mode is drawn in the keyboard's color.
+    pub uses_color: bool,
+    /// Oldest firmware version with the mode, `None` if every firmware the
+    /// applet knows of has it.
+    pub since_firmware: Option<&'static str>,
+}
+
+/// Speed presets of the modes that sweep colors across the keyboard.
+const CYCLE_SPEEDS: [u8; 3] = [40, 120, 220];
+/// Speed presets of the raindrop and splash modes, which look sluggish at
+/// bytes where the cycles already race.
+const REACTIVE_SPEEDS: [u8; 3] = [100, 180, 250];
+
+impl ModeInfo {
+    const fn new(animated: bool, speed_presets: Option<[u8; 3]>, uses_color: bool) -> Self {
+        Self {
+            animated,
+            uses_speed: speed_presets.is_some(),
+            speed_presets: match speed_presets {
+                Some(presets) => presets,
+                None => [0; 3],
+            },
+            uses_color,
+            since_firmware: None,
+        }
+    }
+}
+
+/// [`ModeInfo`] of every mode, indexed by its firmware number.
+const MODE_INFO: [ModeInfo; LedMode::COUNT] = [
+    ModeInfo::new(false, None, true),                 // SolidColor
+    ModeInfo::new(false, None, false),                // PerKey
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleAll
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleLeftRight
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleUpDown
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleOutIn
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleOutInDual
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // RainbowMovingChevron
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CyclePinwheel
+    ModeInfo::new(true, Some(CYCLE_SPEEDS), false),   // CycleSpiral
+    ModeInfo::new(true, Some(REACTIVE_SPEEDS), true), // Raindrops
+    ModeInfo::new(true, Some(REACTIVE_SPEEDS), true), // Splash
+    ModeInfo::new(true, Some(REACTIVE_SPEEDS), true), // Multisplash
+    ModeInfo::new(false, None, true),                 // ActiveKeys
+    ModeInfo::new(false, None, false),                // Disabled
+    ModeInfo::new(false, None, false),                // Last
+];
+
+impl LedMode {
+    pub fn info(self) -> ModeInfo {
+        MODE_INFO[self as usize]
+    }
+}
+
+/// LED index the firmware uses to address the whole keyboard.
+const LED_INDEX_ALL: u8 = 0xFF;
+
+/// A keyboard color in the firmware's hue/saturation representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Color {
+    pub hue: u8,
+    pub saturation: u8,
+}
+
+impl Color {
+    /// The color at full brightness as `#rrggbb`.
+    pub fn to_hex(self) -> String {
+        let hue = u32::from(self.hue) * 6;
+        let saturation = u32::from(self.saturation);
+        // Position within the current sixth of the hue circle, 0..=255.
+        let f = hue % 256;
+        let low = 255 - saturation;
+        let falling = 255 - saturation * f / 255;
+        let rising = 255 - saturation * (255 - f) / 255;
+        let (r, g, b) = match hue / 256 {
+            0 => (255, rising, low),
+            1 => (falling, 255, low),
+            2 => (low, 255, rising),
+            3 => (low, falling, 255),
+            4 => (rising, low, 255),
+            _ => (255, low, falling),
+        };
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hue = u32::from(self.hue) * 360 / 255;
+        let saturation = u32::from(self.saturation) * 100 / 255;
+        write!(f, "{hue}°, {saturation}%")
+    }
+}
+
+/// The lighting state of the keyboard at one point in time.
+///
+/// The keymap isn't part of the snapshot yet since reading it needs the
+/// board's matrix layout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Snapshot {
+    pub mode: LedMode,
+    pub speed: u8,
+    pub brightness: u8,
+    pub color: Color,
+}
+
+/// One difference between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Change {
+    Mode { from: LedMode, to: LedMode },
+    Speed { from: u8, to: u8 },
+    Brightness { from: u8, to: u8 },
+    Color { from: Color, to: Color },
+}
+
+impl Snapshot {
+    /// Lists what changed going from `self` to `other`.
+    pub fn diff(&self, other: &Snapshot) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if self.mode != other.mode {
+            changes.push(Change::Mode {
+                from: self.mode,
+                to: other.mode,
+            });
+        }
+        if self.speed != other.speed {
+            changes.push(Change::Speed {
+                from: self.speed,
+                to: other.speed,
+            });
+        }
+        if self.brightness != other.brightness {
+            changes.push(Change::Brightness {
+                from: self.brightness,
+                to: other.brightness,
+            });
+        }
+        if self.color != other.color {
+            changes.push(Change::Color {
+                from: self.color,
+                to: other.color,
+            });
+        }
+        changes
+    }
+}
+
+/// Counters for the traffic a [`Launch`] handle sent to the EC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EcStats {
+    pub commands: u64,
+    pub errors: u64,
+}
+
+impl std::ops::Add for EcStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            commands: self.commands + other.commands,
+            errors: self.errors + other.errors,
+        }
+    }
+}
+
+/// How the EC link has been doing lately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LinkHealth {
+    Healthy,
+    /// Commands take long enough, usually because the HID transport retried
+    /// them, that changes visibly lag.
+    Slow,
+    /// Calls failed recently.
+    Degraded {
+        errors: usize,
+    },
+}
+
+/// How many of the latest EC calls [`Launch::health`] looks at.
+const RECENT_CALLS: usize = 32;
+/// How long a call counts as recent.
+const RECENT_FOR: Duration = Duration::from_secs(30);
+/// Average time per command above which the link counts as slow.
+const SLOW_COMMAND: Duration = Duration::from_millis(40);
+/// Number of distinct firmware quirks kept, dropping the oldest.
+const MAX_QUIRKS: usize = 16;
+
+/// One call to the EC, kept for [`Launch::health`].
+#[derive(Debug, Clone, Copy)]
+struct CallRecord {
+    at: Instant,
+    elapsed: Duration,
+    commands: u64,
+    failed: bool,
+}
+
+/// A connected Launch keyboard.
+///
+/// Every EC command goes through one helper that takes `&mut self`, and the
+/// HID handle isn't `Send`, so a `Launch` can only be driven from the task
+/// that opened it. A command, including a read following a
+/// multi-packet write, always completes before the next one starts.
+///
+/// Handing one to another thread doesn't compile:
+///
+/// ```compile_fail
+/// use cosmic_applet_launch_control::launch::Launch;
+///
+/// fn drive(launch: Launch) {
+///     std::thread::spawn(move || drop(launch));
+/// }
+/// ```
 pub struct Launch {
     ec: Ec<Box<dyn Access>>,
+    /// USB product id, telling the Launch models apart.
+    product_id: u16,
     board: String,
     version: String,
     current_mode: LedMode,
     current_speed: u8,
+    current_brightness: u8,
+    current_color: Color,
+    /// Highest brightness value the firmware accepts.
+    max_brightness: u8,
+    /// Highest brightness value [`Launch::set_brightness`] writes.
+    brightness_limit: u8,
+    /// Position in the USB tree, if sysfs could tell.
+    usb: Option<UsbTopology>,
+    stats: EcStats,
+    /// The latest calls, oldest first.
+    recent: VecDeque<CallRecord>,
+    /// Distinct writes the firmware acknowledged but applied differently, each
+    /// as the change from the requested to the read-back value, oldest first.
+    quirks: Vec<Change>,
+    /// Quirks of the latest write alone, from requested to applied value.
+    mismatches: Vec<Change>,
 }
 
 impl Launch {
@@ -121,8 +380,20 @@ impl Launch {
                 (0x3384, 0x0001..=0x000A, 1) => {
                     let device = info.open_device(&api)?;
                     let access = AccessHid::new(device, 10, 100)?;
+                    let usb = UsbTopology::from_hidraw(&info.path().to_string_lossy())
+                        .map_err(|err| eprintln!("failed to read USB topology: {err}"))
+                        .ok();
 
-                    let (ec, board, version, current_mode, current_speed) = unsafe {
+                    let (
+                        ec,
+                        board,
+                        version,
+                        current_mode,
+                        current_speed,
+                        current_brightness,
+                        current_color,
+                        max_brightness,
+                    ) = unsafe {
                         let mut ec = Ec::new(access)?.into_dyn();
 
                         let data_size = ec.access().data_size();
@@ -146,15 +417,39 @@ impl Launch {
                             (LedMode::try_from(result.0)?, result.1)
                         };
 
-                        (ec, board, version, current_mode, current_speed)
+                        let (current_brightness, max_brightness) =
+                            ec.led_get_value(LED_INDEX_ALL)?;
+
+                        let (hue, saturation, _) = ec.led_get_color(LED_INDEX_ALL)?;
+
+                        (
+                            ec,
+                            board,
+                            version,
+                            current_mode,
+                            current_speed,
+                            current_brightness,
+                            Color { hue, saturation },
+                            max_brightness,
+                        )
                     };
 
                     return Ok(Self {
                         ec,
+                        product_id: info.product_id(),
                         board,
                         version,
                         current_mode,
                         current_speed,
+                        current_brightness,
+                        current_color,
+                        max_brightness,
+                        brightness_limit: max_brightness,
+                        usb,
+                        stats: EcStats::default(),
+                        recent: VecDeque::new(),
+                        quirks: Vec::new(),
+                        mismatches: Vec::new(),
                     });
                 }
                 _ => {}
@@ -163,6 +458,10 @@ impl Launch {
         Err(LaunchError::DeviceNotFound)
     }
 
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
+
     pub fn board(&self) -> &String {
         &self.board
     }
@@ -179,13 +478,267 @@ impl Launch {
         self.current_speed
     }
 
+    pub fn brightness(&self) -> u8 {
+        self.current_brightness
+    }
+
+    /// The color used by [`LedMode::SolidColor`] and the other modes that
+    /// draw in a single color.
+    pub fn color(&self) -> Color {
+        self.current_color
+    }
+
+    pub fn max_brightness(&self) -> u8 {
+        self.max_brightness
+    }
+
+    pub fn brightness_limit(&self) -> u8 {
+        self.brightness_limit
+    }
+
+    /// Caps the brightness later writes may set, e.g. for light-sensitive
+    /// users. The current brightness is left as it is.
+    pub fn set_brightness_limit(&mut self, limit: u8) {
+        self.brightness_limit = limit.min(self.max_brightness);
+    }
+
+    pub fn usb(&self) -> Option<&UsbTopology> {
+        self.usb.as_ref()
+    }
+
+    pub fn stats(&self) -> EcStats {
+        self.stats
+    }
+
+    /// Runs `commands` EC calls, counting them and whether they failed.
+    fn call<T>(
+        &mut self,
+        commands: u64,
+        f: impl FnOnce(&mut Ec<Box<dyn Access>>) -> Result<T, EcError>,
+    ) -> Result<T, LaunchError> {
+        self.stats.commands += commands;
+        let start = Instant::now();
+        let result = f(&mut self.ec);
+        if result.is_err() {
+            self.stats.errors += 1;
+        }
+        if self.recent.len() == RECENT_CALLS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(CallRecord {
+            at: start,
+            elapsed: start.elapsed(),
+            commands,
+            failed: result.is_err(),
+        });
+        result.map_err(LaunchError::from)
+    }
+
+    /// Judges the link by the calls of the last 30 seconds.
+    pub fn health(&self) -> LinkHealth {
+        let recent = self
+            .recent
+            .iter()
+            .filter(|call| call.at.elapsed() < RECENT_FOR);
+        let errors = recent.clone().filter(|call| call.failed).count();
+        if errors > 0 {
+            return LinkHealth::Degraded { errors };
+        }
+        let (elapsed, commands) = recent.fold((Duration::ZERO, 0), |(elapsed, commands), call| {
+            (elapsed + call.elapsed, commands + call.commands)
+        });
+        if commands > 0 && elapsed / commands as u32 > SLOW_COMMAND {
+            LinkHealth::Slow
+        } else {
+            LinkHealth::Healthy
+        }
+    }
+
+    /// Times one EC round trip that doesn't change anything.
+    pub fn ping(&mut self) -> Result<Duration, LaunchError> {
+        let start = Instant::now();
+        self.call(1, |ec| unsafe { ec.led_get_mode(0) })?;
+        Ok(start.elapsed())
+    }
+
+    /// Reads the LED mode and writes it back unchanged, exercising both
+    /// directions of the link.
+    pub fn echo_mode(&mut self) -> Result<(), LaunchError> {
+        self.call(2, |ec| unsafe {
+            let (mode, speed) = ec.led_get_mode(0)?;
+            ec.led_set_mode(0, mode, speed)
+        })
+    }
+
+    pub fn quirks(&self) -> &[Change] {
+        &self.quirks
+    }
+
+    /// Where the latest write ended up differing from what was requested.
+    /// Empty when the keyboard confirmed every value.
+    pub fn mismatches(&self) -> &[Change] {
+        &self.mismatches
+    }
+
     pub fn set_led_mode(&mut self, mode: LedMode, speed: u8) -> Result<(), LaunchError> {
-        let result = unsafe {
-            self.ec.led_set_mode(0, mode as u8, speed)?;
-            self.ec.led_get_mode(0)?
-        };
+        self.mismatches.clear();
+        self.write_mode(mode, speed)
+    }
+
+    fn write_mode(&mut self, mode: LedMode, speed: u8) -> Result<(), LaunchError> {
+        let result = self.call(2, |ec| unsafe {
+            ec.led_set_mode(0, mode as u8, speed)?;
+            ec.led_get_mode(0)
+        })?;
         self.current_mode = LedMode::try_from(result.0)?;
         self.current_speed = result.1;
+        self.verify(mode, self.current_mode, |from, to| Change::Mode {
+            from,
+            to,
+        });
+        self.verify(speed, self.current_speed, |from, to| Change::Speed {
+            from,
+            to,
+        });
+        Ok(())
+    }
+
+    /// Sets the brightness of every LED, from 0 to [`Launch::max_brightness`].
+    /// Values above the [`Launch::brightness_limit`] are lowered to it.
+    pub fn set_brightness(&mut self, brightness: u8) -> Result<(), LaunchError> {
+        self.mismatches.clear();
+        self.write_brightness(brightness)
+    }
+
+    fn write_brightness(&mut self, brightness: u8) -> Result<(), LaunchError> {
+        let brightness = brightness.min(self.brightness_limit);
+        let (applied, _max) = self.call(2, |ec| unsafe {
+            ec.led_set_value(LED_INDEX_ALL, brightness)?;
+            ec.led_get_value(LED_INDEX_ALL)
+        })?;
+        self.current_brightness = applied;
+        self.verify(brightness, applied, |from, to| Change::Brightness {
+            from,
+            to,
+        });
+        Ok(())
+    }
+
+    /// Sets the color of every LED. The firmware's third color byte is
+    /// written back as it was read.
+    pub fn set_color(&mut self, color: Color) -> Result<(), LaunchError> {
+        self.mismatches.clear();
+        self.write_color(color)
+    }
+
+    fn write_color(&mut self, color: Color) -> Result<(), LaunchError> {
+        let (hue, saturation, _) = self.call(3, |ec| unsafe {
+            let (_, _, extra) = ec.led_get_color(LED_INDEX_ALL)?;
+            ec.led_set_color(LED_INDEX_ALL, color.hue, color.saturation, extra)?;
+            ec.led_get_color(LED_INDEX_ALL)
+        })?;
+        self.current_color = Color { hue, saturation };
+        self.verify(color, self.current_color, |from, to| Change::Color {
+            from,
+            to,
+        });
+        Ok(())
+    }
+
+    /// Stores the current lighting in the keyboard's flash, so it comes back
+    /// after unplugging, including on other machines.
+    pub fn save_to_keyboard(&mut self) -> Result<(), LaunchError> {
+        self.call(1, |ec| unsafe { ec.led_save() })
+    }
+
+    /// Stops or resumes sending key presses to the host. The keyboard keeps
+    /// blocking input until told otherwise or power cycled.
+    pub fn set_input_blocked(&mut self, blocked: bool) -> Result<(), LaunchError> {
+        self.call(1, |ec| unsafe { ec.set_no_input(blocked) })
+    }
+
+    /// Re-reads the mode, speed, brightness and color and updates the cached
+    /// values if another program changed them. Returns whether they had
+    /// drifted.
+    pub fn sync(&mut self) -> Result<bool, LaunchError> {
+        let Snapshot {
+            mode,
+            speed,
+            brightness,
+            color,
+        } = self.snapshot()?;
+        let drifted = mode != self.current_mode
+            || speed != self.current_speed
+            || brightness != self.current_brightness
+            || color != self.current_color;
+        self.current_mode = mode;
+        self.current_speed = speed;
+        self.current_brightness = brightness;
+        self.current_color = color;
+        Ok(drifted)
+    }
+
+    /// Applies every setting of `profile` as one operation, so callers don't
+    /// orchestrate the individual EC calls. If a write fails, the settings
+    /// are rolled back to what they were before.
+    pub fn apply(&mut self, profile: &Profile) -> Result<(), LaunchError> {
+        let before = Profile::from_launch(String::new(), self);
+        self.mismatches.clear();
+        if let Err(err) = self.write_profile(profile) {
+            if let Err(rollback) = self.write_profile(&before) {
+                eprintln!("failed to roll back profile {}: {rollback}", profile.name);
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn write_profile(&mut self, profile: &Profile) -> Result<(), LaunchError> {
+        self.write_mode(profile.mode, profile.speed)?;
+        if let Some(brightness) = profile.brightness {
+            self.write_brightness(brightness)?;
+        }
+        if let Some(color) = profile.color {
+            self.write_color(color)?;
+        }
         Ok(())
     }
+
+    /// Compares a requested value with the one read back after writing it and
+    /// records a quirk when they differ, instead of trusting the write.
+    fn verify<T: PartialEq>(
+        &mut self,
+        requested: T,
+        applied: T,
+        change: impl FnOnce(T, T) -> Change,
+    ) {
+        if requested != applied {
+            let quirk = change(requested, applied);
+            self.mismatches.push(quirk);
+            if self.quirks.contains(&quirk) {
+                return;
+            }
+            eprintln!("firmware applied a different value than requested: {quirk:?}");
+            if self.quirks.len() == MAX_QUIRKS {
+                self.quirks.remove(0);
+            }
+            self.quirks.push(quirk);
+        }
+    }
+
+    /// Reads the full lighting state back from the EC.
+    pub fn snapshot(&mut self) -> Result<Snapshot, LaunchError> {
+        let (mode, speed, brightness, color) = self.call(3, |ec| unsafe {
+            let (mode, speed) = ec.led_get_mode(0)?;
+            let (brightness, _max) = ec.led_get_value(LED_INDEX_ALL)?;
+            let (hue, saturation, _) = ec.led_get_color(LED_INDEX_ALL)?;
+            Ok((mode, speed, brightness, Color { hue, saturation }))
+        })?;
+        Ok(Snapshot {
+            mode: LedMode::try_from(mode)?,
+            speed,
+            brightness,
+            color,
+        })
+    }
 }
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Talking to System76 Launch keyboards: reading and changing the lighting,
+//! saving it as profiles and following hotplug. The applet is built on top
+//! of this, and `examples/` shows it used on its own.
+//!
+//! Errors, events, LED modes and the structs the keyboard reports are
+//! `#[non_exhaustive]`, so they can grow without breaking dependents.
+
+pub mod bus;
+pub mod device_listener;
+pub mod launch;
+pub mod localization;
+pub mod profile;
+pub mod shutdown;
+pub mod usb;
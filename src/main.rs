@@ -1,11 +1,26 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use app::LaunchControl;
+use cosmic_applet_launch_control::{bus, device_listener, fl, launch, profile, shutdown, usb};
 /// The `app` module is used by convention to indicate the main component of our application.
 mod app;
-mod device_listener;
-mod launch;
-mod localization;
+mod config;
+mod conflicts;
+mod diagnostics;
+mod format;
+mod indicators;
+mod kmsg;
+mod notify;
+mod playlist;
+mod power;
+mod presets;
+mod privacy;
+mod rules;
+mod scene;
+mod search;
+mod session;
+mod supervisor;
+mod widgets;
 
 /// The `cosmic::app::run()` function is the starting point of your application.
 /// It takes two arguments:
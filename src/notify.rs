@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// How long the notification daemon may take to accept a notification.
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Shows a desktop notification through `notify-send`. Failures are only
+/// logged, since the keyboard already signals whatever this is about.
+pub async fn send(summary: String, body: String) {
+    let output = Command::new("notify-send")
+        .args([
+            "--app-name=Launch Control",
+            "--icon=input-keyboard-symbolic",
+        ])
+        .arg(summary)
+        .arg(body)
+        .output();
+    match tokio::time::timeout(SEND_TIMEOUT, output).await {
+        Ok(Ok(output)) if !output.status.success() => eprintln!(
+            "notify-send failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => eprintln!("failed to run notify-send: {err}"),
+        Err(_) => eprintln!("notify-send timed out"),
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::profile::Profile;
+
+/// A profile shown for a while as part of a [`Playlist`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub profile: String,
+    pub seconds: u32,
+}
+
+/// Profiles shown one after another, starting over after the last one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Playlist {
+    pub name: String,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// A playlist that is being played.
+#[derive(Debug)]
+pub struct Playback {
+    /// Index of the playlist in the config.
+    pub playlist: usize,
+    /// Index of the entry showing.
+    pub entry: usize,
+    /// When the next entry is due.
+    pub next_at: Instant,
+    /// Lighting from before the playlist started, put back when it stops.
+    pub restore: Option<Profile>,
+}
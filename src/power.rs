@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::launch::{LedMode, Snapshot};
+
+/// Power one key's LED draws at full brightness, in milliwatts. Measured
+/// values vary with color; this is the white worst case, rounded up.
+const MILLIWATTS_PER_KEY: u32 = 60;
+
+/// Key counts per board, matched against the board name reported by the EC.
+/// The longest matching prefix wins, so the order doesn't matter.
+const KEY_COUNTS: &[(&str, u32)] = &[
+    ("system76/launch_heavy", 105),
+    ("system76/launch_lite", 84),
+    ("system76/launch", 84),
+];
+
+/// Keys assumed when the board isn't in [`KEY_COUNTS`].
+const DEFAULT_KEY_COUNT: u32 = 84;
+
+/// Estimates the LED power draw of a board in milliwatts.
+///
+/// This is a simple model: every lit key draws a share of
+/// [`MILLIWATTS_PER_KEY`] proportional to the brightness. Effects that only
+/// light a few keys at a time are counted as fully lit, so the estimate errs
+/// high.
+pub fn estimate_milliwatts(board: &str, snapshot: &Snapshot, max_brightness: u8) -> u32 {
+    if snapshot.mode == LedMode::Disabled || max_brightness == 0 {
+        return 0;
+    }
+    let keys = KEY_COUNTS
+        .iter()
+        .filter(|(prefix, _)| board.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(DEFAULT_KEY_COUNT, |(_, keys)| *keys);
+    keys * MILLIWATTS_PER_KEY * u32::from(snapshot.brightness) / u32::from(max_brightness)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::launch::Color;
+
+    use super::*;
+
+    fn snapshot(mode: LedMode, brightness: u8) -> Snapshot {
+        Snapshot {
+            mode,
+            speed: 128,
+            brightness,
+            color: Color {
+                hue: 0,
+                saturation: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn estimates() {
+        for (board, mode, brightness, milliwatts) in [
+            ("system76/launch_1", LedMode::SolidColor, 255, 84 * 60),
+            ("system76/launch_2", LedMode::SolidColor, 0, 0),
+            ("system76/launch_lite_1", LedMode::CycleAll, 255, 84 * 60),
+            (
+                "system76/launch_heavy_1",
+                LedMode::SolidColor,
+                255,
+                105 * 60,
+            ),
+            ("system76/launch_heavy_1", LedMode::SolidColor, 51, 105 * 12),
+            ("system76/launch_heavy_1", LedMode::Disabled, 255, 0),
+            (
+                "other/board",
+                LedMode::SolidColor,
+                255,
+                DEFAULT_KEY_COUNT * 60,
+            ),
+        ] {
+            assert_eq!(
+                estimate_milliwatts(board, &snapshot(mode, brightness), 255),
+                milliwatts,
+                "{board} {mode:?} at {brightness}"
+            );
+        }
+    }
+
+    #[test]
+    fn no_brightness_range_draws_nothing() {
+        let snapshot = snapshot(LedMode::SolidColor, 0);
+        assert_eq!(estimate_milliwatts("system76/launch_1", &snapshot, 0), 0);
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use rust_embed::RustEmbed;
+
+use crate::profile::Profile;
+
+#[derive(RustEmbed)]
+#[folder = "res/presets/"]
+struct Bundled;
+
+/// Somewhere example profiles come from.
+///
+/// Presets use the regular profile file format, so a remote gallery only has
+/// to fetch files and feed them through [`Profile::import`].
+pub trait PresetSource {
+    fn presets(&self) -> Vec<Profile>;
+}
+
+/// Presets shipped inside the applet binary.
+pub struct BundledPresets;
+
+impl PresetSource for BundledPresets {
+    fn presets(&self) -> Vec<Profile> {
+        let mut presets: Vec<Profile> = Bundled::iter()
+            .filter_map(|path| {
+                let file = Bundled::get(&path)?;
+                let text = String::from_utf8_lossy(&file.data);
+                match Profile::import(&text) {
+                    Ok(profile) => Some(profile),
+                    Err(err) => {
+                        eprintln!("skipping preset {path}: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        presets.sort_by(|a, b| a.name.cmp(&b.name));
+        presets
+    }
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fs;
+use std::path::Path;
+
+/// Which devices applications are using, as far as rules need to know.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceUsage {
+    pub microphone: bool,
+    pub camera: bool,
+}
+
+impl DeviceUsage {
+    /// Checks the devices asked for on a blocking thread, since finding an
+    /// open camera walks the open files of every process. Devices that
+    /// weren't asked for read as unused.
+    pub async fn read(microphone: bool, camera: bool) -> Self {
+        tokio::task::spawn_blocking(move || Self {
+            microphone: microphone && microphone_in_use(),
+            camera: camera && camera_in_use(),
+        })
+        .await
+        .unwrap_or_default()
+    }
+}
+
+/// Whether any ALSA capture stream is running. PipeWire and PulseAudio only
+/// keep a capture device running while some client records from it.
+pub fn microphone_in_use() -> bool {
+    let Ok(cards) = fs::read_dir("/proc/asound") else {
+        return false;
+    };
+
+    cards.flatten().any(|card| {
+        let Ok(pcms) = fs::read_dir(card.path()) else {
+            return false;
+        };
+        pcms.flatten()
+            .filter(|pcm| pcm.file_name().to_string_lossy().ends_with('c'))
+            .any(|pcm| {
+                let Ok(substreams) = fs::read_dir(pcm.path()) else {
+                    return false;
+                };
+                substreams.flatten().any(|substream| {
+                    fs::read_to_string(substream.path().join("status"))
+                        .is_ok_and(|status| status.starts_with("state: RUNNING"))
+                })
+            })
+    })
+}
+
+/// Whether any process we can inspect holds a video device open.
+pub fn camera_in_use() -> bool {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return false;
+    };
+
+    processes.flatten().any(|process| {
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            return false;
+        };
+        fds.flatten()
+            .any(|fd| fs::read_link(fd.path()).is_ok_and(|target| is_video_device(&target)))
+    })
+}
+
+fn is_video_device(path: &Path) -> bool {
+    path.parent() == Some(Path::new("/dev"))
+        && path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("video"))
+}
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::launch::{Change, Color, Launch, LaunchError, LedMode};
+
+/// Version written into exported profiles.
+const FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ImportError {
+    #[error("invalid profile file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("unsupported profile file version: {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// A profile as it is stored in files, tagged with the format version.
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileFile {
+    version: u32,
+    profile: Profile,
+}
+
+/// Settings of one effect, keyed by setting name.
+///
+/// Values are kept as loosely typed RON, so settings written by a newer
+/// version survive being loaded and saved again by an older one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EffectConfig(pub BTreeMap<String, ron::Value>);
+
+impl EffectConfig {
+    /// Reads a setting, or `None` if it's missing or has another type.
+    pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.0.get(key)?.clone().into_rust().ok()
+    }
+
+    pub fn set<T: Serialize>(
+        &mut self,
+        key: impl Into<String>,
+        value: &T,
+    ) -> Result<(), ron::Error> {
+        let value = ron::from_str(&ron::to_string(value)?).map_err(|err| err.code)?;
+        self.0.insert(key.into(), value);
+        Ok(())
+    }
+}
+
+/// A named set of lighting settings that can be applied in one go.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Profile {
+    pub name: String,
+    pub mode: LedMode,
+    pub speed: u8,
+    /// Brightness to set, or `None` to leave it as it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    /// Color to set, or `None` to leave it as it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    /// Settings of effects the profile uses, keyed by effect name. Effects
+    /// this version doesn't know are kept as they are.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub effects: BTreeMap<String, EffectConfig>,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>, mode: LedMode, speed: u8) -> Self {
+        Self {
+            name: name.into(),
+            mode,
+            speed,
+            brightness: None,
+            color: None,
+            effects: BTreeMap::new(),
+        }
+    }
+
+    /// Captures the keyboard's current lighting as a profile called `name`.
+    pub fn from_launch(name: impl Into<String>, launch: &Launch) -> Self {
+        Self {
+            brightness: Some(launch.brightness()),
+            color: Some(launch.color()),
+            ..Self::new(name, launch.current_mode(), launch.current_speed())
+        }
+    }
+
+    /// Takes over the lighting `other` sets, keeping the settings it leaves
+    /// as they are.
+    pub fn overlay(&mut self, other: &Profile) {
+        self.mode = other.mode;
+        self.speed = other.speed;
+        self.brightness = other.brightness.or(self.brightness);
+        self.color = other.color.or(self.color);
+    }
+
+    /// Lists the settings that differ going from `self` to `other`.
+    pub fn diff(&self, other: &Profile) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if self.mode != other.mode {
+            changes.push(Change::Mode {
+                from: self.mode,
+                to: other.mode,
+            });
+        }
+        if self.speed != other.speed {
+            changes.push(Change::Speed {
+                from: self.speed,
+                to: other.speed,
+            });
+        }
+        // Settings one side leaves alone don't count as changed.
+        if let (Some(from), Some(to)) = (self.brightness, other.brightness) {
+            if from != to {
+                changes.push(Change::Brightness { from, to });
+            }
+        }
+        if let (Some(from), Some(to)) = (self.color, other.color) {
+            if from != to {
+                changes.push(Change::Color { from, to });
+            }
+        }
+        changes
+    }
+
+    pub fn apply(&self, launch: &mut Launch) -> Result<(), LaunchError> {
+        launch.apply(self)
+    }
+
+    /// Reads a profile from its file format.
+    pub fn import(text: &str) -> Result<Self, ImportError> {
+        let file: ProfileFile = ron::from_str(text)?;
+        if file.version > FILE_VERSION {
+            return Err(ImportError::UnsupportedVersion(file.version));
+        }
+        Ok(file.profile)
+    }
+
+    /// Writes the profile in the format [`Profile::import`] reads.
+    pub fn export(&self) -> Result<String, ron::Error> {
+        let file = ProfileFile {
+            version: FILE_VERSION,
+            profile: self.clone(),
+        };
+        ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUTURE_EFFECTS: &str = r#"(
+        version: 1,
+        profile: (
+            name: "Night",
+            mode: CycleAll,
+            speed: 3,
+            brightness: 40,
+            effects: {
+                "audio": {
+                    "sensitivity": 0.5,
+                    "regions": ["top", "left"],
+                    "future": {"falloff": 2, "shape": "ring"},
+                },
+                "unknown-effect": {"enabled": true},
+            },
+        ),
+    )"#;
+
+    #[test]
+    fn unknown_effect_settings_survive_a_round_trip() {
+        let profile = Profile::import(FUTURE_EFFECTS).unwrap();
+        let audio = &profile.effects["audio"];
+        assert_eq!(audio.get::<f32>("sensitivity"), Some(0.5));
+        assert_eq!(
+            audio.get::<Vec<String>>("regions"),
+            Some(vec!["top".to_string(), "left".to_string()])
+        );
+        assert!(audio.0.contains_key("future"));
+        assert!(profile.effects.contains_key("unknown-effect"));
+
+        let again = Profile::import(&profile.export().unwrap()).unwrap();
+        assert_eq!(again, profile);
+    }
+
+    #[test]
+    fn typed_effect_settings() {
+        let mut config = EffectConfig::default();
+        config.set("sensitivity", &0.75f32).unwrap();
+        config.set("regions", &vec!["top"]).unwrap();
+        assert_eq!(config.get::<f32>("sensitivity"), Some(0.75));
+        assert_eq!(
+            config.get::<Vec<String>>("regions"),
+            Some(vec!["top".to_string()])
+        );
+        assert_eq!(config.get::<String>("sensitivity"), None);
+        assert_eq!(config.get::<f32>("missing"), None);
+    }
+
+    #[test]
+    fn files_without_effects_still_import() {
+        let profile = Profile::import(
+            r#"(version: 1, profile: (name: "Plain", mode: SolidColor, speed: 0))"#,
+        )
+        .unwrap();
+        assert!(profile.effects.is_empty());
+        assert_eq!(profile.brightness, None);
+        assert!(!profile.export().unwrap().contains("effects"));
+    }
+
+    #[test]
+    fn newer_file_versions_are_refused() {
+        let text = FUTURE_EFFECTS.replacen("version: 1", "version: 2", 1);
+        assert!(matches!(
+            Profile::import(&text),
+            Err(ImportError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,616 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::fs;
+
+use chrono::{Datelike, Local, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::fl;
+use crate::format;
+use crate::indicators::IndicatorSource;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// A condition that makes a rule apply its profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    DeviceConnected,
+    /// An application with the given app id has focus.
+    AppFocused(String),
+    /// The local time is inside the range, in minutes since midnight. Ranges
+    /// where `end` is before `start` wrap around midnight.
+    TimeRange {
+        start: u16,
+        end: u16,
+        /// The days the range applies on, judged by the current day.
+        #[serde(default)]
+        days: Days,
+    },
+    OnBattery,
+    Locked,
+    /// COSMIC's notification Do Not Disturb mode is on.
+    DoNotDisturb,
+    /// Some application records from a microphone.
+    MicrophoneInUse,
+    /// Some application has a camera open.
+    CameraInUse,
+    /// The indicator source reports a non-zero value.
+    Indicator(IndicatorSource),
+}
+
+/// Which days a [`Trigger::TimeRange`] applies on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum Days {
+    #[default]
+    Every,
+    Weekdays,
+    Weekends,
+}
+
+impl Days {
+    pub fn all() -> Vec<Self> {
+        Self::iter().collect()
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Every => fl!("days-every"),
+            Self::Weekdays => fl!("days-weekdays"),
+            Self::Weekends => fl!("days-weekends"),
+        }
+    }
+
+    fn includes(self, weekend: bool) -> bool {
+        match self {
+            Self::Every => true,
+            Self::Weekdays => !weekend,
+            Self::Weekends => weekend,
+        }
+    }
+}
+
+/// The kinds of [`Trigger`], used to pick one in the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum TriggerKind {
+    DeviceConnected,
+    AppFocused,
+    TimeRange,
+    OnBattery,
+    Locked,
+    DoNotDisturb,
+    MicrophoneInUse,
+    CameraInUse,
+    Script,
+}
+
+impl TriggerKind {
+    /// The kinds offered in the editor. App focus isn't one of them since
+    /// nothing reports the focused app yet.
+    pub fn all() -> Vec<Self> {
+        Self::iter()
+            .filter(|kind| *kind != Self::AppFocused)
+            .collect()
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::DeviceConnected => fl!("trigger-device-connected"),
+            Self::AppFocused => fl!("trigger-app-focused"),
+            Self::TimeRange => fl!("trigger-time-range"),
+            Self::OnBattery => fl!("trigger-on-battery"),
+            Self::Locked => fl!("trigger-locked"),
+            Self::DoNotDisturb => fl!("trigger-do-not-disturb"),
+            Self::MicrophoneInUse => fl!("trigger-microphone-in-use"),
+            Self::CameraInUse => fl!("trigger-camera-in-use"),
+            Self::Script => fl!("trigger-script"),
+        }
+    }
+
+    /// Whether triggers of this kind need a parameter typed in the editor.
+    pub fn has_param(self) -> bool {
+        matches!(self, Self::AppFocused | Self::TimeRange | Self::Script)
+    }
+
+    /// Builds a trigger of this kind from the editor's parameter field.
+    /// `days` only matters for time ranges.
+    pub fn build(self, param: &str, days: Days) -> Option<Trigger> {
+        match self {
+            Self::DeviceConnected => Some(Trigger::DeviceConnected),
+            Self::AppFocused => {
+                let app_id = param.trim();
+                (!app_id.is_empty()).then(|| Trigger::AppFocused(app_id.to_string()))
+            }
+            Self::TimeRange => {
+                let (start, end) = param.split_once('-')?;
+                Some(Trigger::TimeRange {
+                    start: parse_time(start)?,
+                    end: parse_time(end)?,
+                    days,
+                })
+            }
+            Self::OnBattery => Some(Trigger::OnBattery),
+            Self::Locked => Some(Trigger::Locked),
+            Self::DoNotDisturb => Some(Trigger::DoNotDisturb),
+            Self::MicrophoneInUse => Some(Trigger::MicrophoneInUse),
+            Self::CameraInUse => Some(Trigger::CameraInUse),
+            Self::Script => {
+                let command = param.trim();
+                (!command.is_empty())
+                    .then(|| Trigger::Indicator(IndicatorSource::Script(command.to_string())))
+            }
+        }
+    }
+}
+
+impl Trigger {
+    pub fn kind(&self) -> TriggerKind {
+        match self {
+            Self::DeviceConnected => TriggerKind::DeviceConnected,
+            Self::AppFocused(_) => TriggerKind::AppFocused,
+            Self::TimeRange { .. } => TriggerKind::TimeRange,
+            Self::OnBattery => TriggerKind::OnBattery,
+            Self::Locked => TriggerKind::Locked,
+            Self::DoNotDisturb => TriggerKind::DoNotDisturb,
+            Self::MicrophoneInUse => TriggerKind::MicrophoneInUse,
+            Self::CameraInUse => TriggerKind::CameraInUse,
+            Self::Indicator(IndicatorSource::Script(_)) => TriggerKind::Script,
+        }
+    }
+
+    pub fn matches(&self, context: &Context) -> bool {
+        match self {
+            Self::DeviceConnected => context.device_connected,
+            Self::AppFocused(app_id) => context.focused_app.as_deref() == Some(app_id.as_str()),
+            Self::TimeRange { .. } => self.minutes_left(context).is_some(),
+            Self::OnBattery => context.on_battery,
+            Self::Locked => context.locked,
+            Self::DoNotDisturb => context.do_not_disturb,
+            Self::MicrophoneInUse => context.microphone_in_use,
+            Self::CameraInUse => context.camera_in_use,
+            Self::Indicator(source) => context.indicators.get(source).is_some_and(|v| *v > 0),
+        }
+    }
+
+    /// For a time range that holds in `context`, the minutes until it ends.
+    pub fn minutes_left(&self, context: &Context) -> Option<u16> {
+        let Self::TimeRange { start, end, days } = *self else {
+            return None;
+        };
+        if !days.includes(context.weekend) {
+            return None;
+        }
+
+        let now = context.minutes;
+        let inside = if start <= end {
+            (start..end).contains(&now)
+        } else {
+            now >= start || now < end
+        };
+        inside.then(|| {
+            if end > now {
+                end - now
+            } else {
+                end + MINUTES_PER_DAY - now
+            }
+        })
+    }
+
+    /// For a time range, the minutes until its current window ends or, when
+    /// it doesn't hold, until the next window on a day it applies ends.
+    pub fn minutes_to_window_end(&self, context: &Context) -> Option<u32> {
+        if let Some(left) = self.minutes_left(context) {
+            return Some(left.into());
+        }
+        let Self::TimeRange { start, end, days } = *self else {
+            return None;
+        };
+        if start == end {
+            return None;
+        }
+
+        let length = u32::from((end + MINUTES_PER_DAY - start) % MINUTES_PER_DAY);
+        let until_start = u32::from((start + MINUTES_PER_DAY - context.minutes) % MINUTES_PER_DAY);
+        (0..=7)
+            .map(|day| until_start + day * u32::from(MINUTES_PER_DAY))
+            .find(|minutes| {
+                days == Days::Every
+                    || days.includes(is_weekend_at(context.timestamp + i64::from(*minutes) * 60))
+            })
+            .map(|minutes| minutes + length)
+    }
+
+    /// Whether the trigger reflects state that changes within seconds and
+    /// needs polling more often than once a minute.
+    pub fn is_realtime(&self) -> bool {
+        matches!(
+            self,
+            Self::Locked | Self::MicrophoneInUse | Self::CameraInUse
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::AppFocused(app_id) => format!("{}: {app_id}", self.kind().label()),
+            Self::Indicator(IndicatorSource::Script(command)) => {
+                format!("{}: {command}", self.kind().label())
+            }
+            Self::TimeRange { start, end, days } => {
+                let range = format!(
+                    "{}: {}-{}",
+                    self.kind().label(),
+                    format::time(*start),
+                    format::time(*end)
+                );
+                match days {
+                    Days::Every => range,
+                    _ => format!("{range} ({})", days.label().to_lowercase()),
+                }
+            }
+            _ => self.kind().label(),
+        }
+    }
+}
+
+/// Applies the named profile while its trigger holds. When several rules
+/// match, the one with the highest priority wins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub trigger: Trigger,
+    pub profile: String,
+    pub priority: i32,
+    pub enabled: bool,
+    /// Unix timestamp until which the rule is skipped, for one-off exceptions
+    /// like "not tonight".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_until: Option<i64>,
+}
+
+impl Rule {
+    pub fn is_skipped(&self, context: &Context) -> bool {
+        self.skip_until
+            .is_some_and(|until| context.timestamp < until)
+    }
+
+    /// When skipping the rule now would end: the end of its current time
+    /// window, or of the next one when it doesn't hold. `None` if the rule
+    /// isn't a time range.
+    pub fn skip_target(&self, context: &Context) -> Option<i64> {
+        let minutes = self.trigger.minutes_to_window_end(context)?;
+        Some(context.timestamp + i64::from(minutes) * 60)
+    }
+}
+
+/// The state of the world the rules are evaluated against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    pub device_connected: bool,
+    /// App id of the focused window. Nothing reports this yet, so
+    /// [`Trigger::AppFocused`] rules only match in simulations.
+    pub focused_app: Option<String>,
+    /// Local time in minutes since midnight.
+    pub minutes: u16,
+    /// Whether today is a Saturday or Sunday.
+    pub weekend: bool,
+    /// The current time as a Unix timestamp.
+    pub timestamp: i64,
+    pub on_battery: bool,
+    /// Whether the session is locked, as logind reports it. Only checked
+    /// while a rule needs it.
+    pub locked: bool,
+    pub do_not_disturb: bool,
+    /// Whether some application records from a microphone. Only checked
+    /// while a rule needs it, see [`uses`].
+    pub microphone_in_use: bool,
+    /// Whether some application has a camera open, checked like the
+    /// microphone.
+    pub camera_in_use: bool,
+    /// Latest readings of the indicator sources used by rules. Reading them
+    /// can be slow, so callers do it asynchronously and fill this in.
+    pub indicators: HashMap<IndicatorSource, u32>,
+}
+
+impl Context {
+    /// Builds a context from the current time and power state. Callers fill
+    /// in the state they track or read asynchronously themselves.
+    pub fn current(device_connected: bool) -> Self {
+        let now = Local::now();
+        Self {
+            device_connected,
+            minutes: (now.hour() * 60 + now.minute()) as u16,
+            weekend: matches!(now.weekday(), Weekday::Sat | Weekday::Sun),
+            timestamp: now.timestamp(),
+            on_battery: on_battery(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether the local day at `timestamp` falls on a weekend.
+fn is_weekend_at(timestamp: i64) -> bool {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .is_some_and(|time| matches!(time.weekday(), Weekday::Sat | Weekday::Sun))
+}
+
+/// Whether the system runs on battery, i.e. it has a battery and no mains
+/// supply is online.
+fn on_battery() -> bool {
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    let mut has_battery = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" => {
+                if fs::read_to_string(path.join("online")).is_ok_and(|online| online.trim() == "1")
+                {
+                    return false;
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+    has_battery
+}
+
+/// The outcome of evaluating the rules against a [`Context`].
+#[derive(Debug, Default)]
+pub struct Evaluation<'a> {
+    /// The enabled, matching rule with the highest priority.
+    pub winner: Option<&'a Rule>,
+    /// Other enabled rules that matched but lost to the winner.
+    pub overridden: Vec<&'a Rule>,
+}
+
+impl Evaluation<'_> {
+    /// Explains in plain words which rule decides the lighting and why.
+    pub fn explain(&self) -> String {
+        let Some(winner) = self.winner else {
+            return fl!("explain-no-rule");
+        };
+
+        let mut explanation = fl!(
+            "explain-winner",
+            trigger = winner.trigger.describe(),
+            profile = winner.profile.as_str(),
+            priority = winner.priority
+        );
+        for rule in &self.overridden {
+            explanation.push('\n');
+            explanation.push_str(&fl!(
+                "explain-overridden",
+                trigger = rule.trigger.describe(),
+                profile = rule.profile.as_str(),
+                priority = rule.priority
+            ));
+        }
+        explanation
+    }
+}
+
+/// Finds the rule that should decide the lighting in `context`.
+///
+/// Conflicts are resolved by priority; between rules of equal priority the one
+/// listed first wins. This has no side effects, so it doubles as a dry run.
+pub fn evaluate<'a>(rules: &'a [Rule], context: &Context) -> Evaluation<'a> {
+    let mut evaluation = Evaluation::default();
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.enabled && !rule.is_skipped(context) && rule.trigger.matches(context))
+    {
+        match evaluation.winner {
+            Some(winner) if winner.priority >= rule.priority => evaluation.overridden.push(rule),
+            Some(winner) => {
+                evaluation.overridden.push(winner);
+                evaluation.winner = Some(rule);
+            }
+            None => evaluation.winner = Some(rule),
+        }
+    }
+    evaluation
+}
+
+/// Parses `HH:MM`, or `h:MM AM` and `h:MM PM` as 12-hour clocks show it,
+/// into minutes since midnight.
+pub fn parse_time(text: &str) -> Option<u16> {
+    let text = text.trim().to_ascii_uppercase();
+    let (clock, afternoon) = match (text.strip_suffix("AM"), text.strip_suffix("PM")) {
+        (Some(clock), _) => (clock.trim_end(), Some(false)),
+        (_, Some(clock)) => (clock.trim_end(), Some(true)),
+        _ => (text.as_str(), None),
+    };
+    let (hours, minutes) = clock.split_once(':')?;
+    let mut hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    if let Some(afternoon) = afternoon {
+        if !(1..=12).contains(&hours) {
+            return None;
+        }
+        hours = hours % 12 + if afternoon { 12 } else { 0 };
+    }
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Whether an enabled rule has a trigger of `kind`.
+pub fn uses(rules: &[Rule], kind: TriggerKind) -> bool {
+    rules
+        .iter()
+        .any(|rule| rule.enabled && rule.trigger.kind() == kind)
+}
+
+/// The indicator sources enabled rules depend on.
+pub fn indicator_sources(rules: &[Rule]) -> Vec<IndicatorSource> {
+    let mut sources = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        if let Trigger::Indicator(source) = &rule.trigger {
+            if !sources.contains(source) {
+                sources.push(source.clone());
+            }
+        }
+    }
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(trigger: Trigger, profile: &str, priority: i32) -> Rule {
+        Rule {
+            trigger,
+            profile: profile.to_string(),
+            priority,
+            enabled: true,
+            skip_until: None,
+        }
+    }
+
+    fn range(start: &str, end: &str, days: Days) -> Trigger {
+        Trigger::TimeRange {
+            start: parse_time(start).unwrap(),
+            end: parse_time(end).unwrap(),
+            days,
+        }
+    }
+
+    fn at(time: &str) -> Context {
+        Context {
+            device_connected: true,
+            minutes: parse_time(time).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn highest_priority_wins() {
+        let rules = [
+            rule(Trigger::DeviceConnected, "low", 1),
+            rule(Trigger::DeviceConnected, "high", 5),
+            rule(Trigger::OnBattery, "unmatched", 10),
+        ];
+        let evaluation = evaluate(&rules, &at("12:00"));
+        assert_eq!(evaluation.winner.unwrap().profile, "high");
+        let overridden: Vec<_> = evaluation.overridden.iter().map(|r| &r.profile).collect();
+        assert_eq!(overridden, ["low"]);
+    }
+
+    #[test]
+    fn first_listed_wins_ties() {
+        let rules = [
+            rule(Trigger::DeviceConnected, "first", 3),
+            rule(Trigger::DeviceConnected, "second", 3),
+        ];
+        assert_eq!(
+            evaluate(&rules, &at("12:00")).winner.unwrap().profile,
+            "first"
+        );
+    }
+
+    #[test]
+    fn disabled_rules_never_win() {
+        let mut disabled = rule(Trigger::DeviceConnected, "disabled", 9);
+        disabled.enabled = false;
+        let rules = [disabled, rule(Trigger::DeviceConnected, "enabled", 1)];
+        assert_eq!(
+            evaluate(&rules, &at("12:00")).winner.unwrap().profile,
+            "enabled"
+        );
+    }
+
+    #[test]
+    fn skipped_rules_sit_out_until_the_skip_ends() {
+        let mut skipped = rule(Trigger::DeviceConnected, "skipped", 1);
+        skipped.skip_until = Some(1_000);
+        let rules = [skipped];
+
+        let mut context = at("12:00");
+        context.timestamp = 999;
+        assert!(evaluate(&rules, &context).winner.is_none());
+        context.timestamp = 1_000;
+        assert!(evaluate(&rules, &context).winner.is_some());
+    }
+
+    #[test]
+    fn day_filters() {
+        let weekdays = range("09:00", "17:00", Days::Weekdays);
+        let weekends = range("09:00", "17:00", Days::Weekends);
+        let every = range("09:00", "17:00", Days::Every);
+
+        let monday = at("10:00");
+        let saturday = Context {
+            weekend: true,
+            ..at("10:00")
+        };
+        assert!(weekdays.matches(&monday));
+        assert!(!weekdays.matches(&saturday));
+        assert!(!weekends.matches(&monday));
+        assert!(weekends.matches(&saturday));
+        assert!(every.matches(&monday) && every.matches(&saturday));
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("00:00"), Some(0));
+        assert_eq!(parse_time(" 07:30 "), Some(450));
+        assert_eq!(parse_time("23:59"), Some(1439));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("noon"), None);
+        assert_eq!(parse_time("12"), None);
+        assert_eq!(parse_time("12:00 AM"), Some(0));
+        assert_eq!(parse_time("9:30 am"), Some(570));
+        assert_eq!(parse_time("12:15 PM"), Some(735));
+        assert_eq!(parse_time("11:59PM"), Some(1439));
+        assert_eq!(parse_time("13:00 PM"), None);
+        assert_eq!(parse_time("0:30 AM"), None);
+    }
+
+    #[test]
+    fn minutes_left_in_a_range() {
+        let day = range("09:00", "17:00", Days::Every);
+        assert_eq!(day.minutes_left(&at("09:00")), Some(480));
+        assert_eq!(day.minutes_left(&at("16:59")), Some(1));
+        assert_eq!(day.minutes_left(&at("17:00")), None);
+        assert_eq!(day.minutes_left(&at("08:59")), None);
+    }
+
+    #[test]
+    fn minutes_left_across_midnight() {
+        let night = range("22:00", "07:00", Days::Every);
+        assert_eq!(night.minutes_left(&at("22:00")), Some(540));
+        assert_eq!(night.minutes_left(&at("23:00")), Some(480));
+        assert_eq!(night.minutes_left(&at("00:00")), Some(420));
+        assert_eq!(night.minutes_left(&at("06:59")), Some(1));
+        assert_eq!(night.minutes_left(&at("07:00")), None);
+        assert_eq!(night.minutes_left(&at("12:00")), None);
+    }
+
+    #[test]
+    fn skip_target_is_the_end_of_the_window() {
+        let night = rule(range("22:00", "07:00", Days::Every), "night", 0);
+        let mut context = at("23:00");
+        context.timestamp = 10_000;
+        assert_eq!(night.skip_target(&context), Some(10_000 + 480 * 60));
+    }
+
+    #[test]
+    fn skip_target_outside_is_the_end_of_the_next_window() {
+        let morning = rule(range("07:00", "09:00", Days::Every), "morning", 0);
+        let mut context = at("12:00");
+        context.timestamp = 10_000;
+        // 19 hours until 07:00, then the two hours of the window.
+        assert_eq!(morning.skip_target(&context), Some(10_000 + 21 * 60 * 60));
+
+        let never = rule(range("07:00", "07:00", Days::Every), "never", 0);
+        assert_eq!(never.skip_target(&context), None);
+        let device = rule(Trigger::DeviceConnected, "device", 0);
+        assert_eq!(device.skip_target(&context), None);
+    }
+}
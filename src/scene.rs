@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{Rule, Trigger};
+
+/// A named combination of lighting and automation that is switched as a
+/// unit, like a "Streaming" scene that turns on the microphone rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+    /// Profile applied when the scene is activated, if any.
+    pub profile: Option<String>,
+    /// Enabled state of the rules at the time the scene was saved.
+    pub rules: Vec<RuleState>,
+}
+
+/// Whether one rule is enabled. Rules are matched by trigger and profile
+/// since they have no ids of their own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleState {
+    pub trigger: Trigger,
+    pub profile: String,
+    pub enabled: bool,
+}
+
+impl Scene {
+    /// Captures which of `rules` are enabled right now.
+    pub fn capture(name: String, profile: Option<String>, rules: &[Rule]) -> Self {
+        Self {
+            name,
+            profile,
+            rules: rules
+                .iter()
+                .map(|rule| RuleState {
+                    trigger: rule.trigger.clone(),
+                    profile: rule.profile.clone(),
+                    enabled: rule.enabled,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns `rules` with the scene's enabled states applied. Rules added
+    /// after the scene was saved keep their current state.
+    pub fn apply_to(&self, rules: &[Rule]) -> Vec<Rule> {
+        rules
+            .iter()
+            .map(|rule| {
+                let state = self
+                    .rules
+                    .iter()
+                    .find(|state| state.trigger == rule.trigger && state.profile == rule.profile);
+                Rule {
+                    enabled: state.map_or(rule.enabled, |state| state.enabled),
+                    ..rule.clone()
+                }
+            })
+            .collect()
+    }
+}
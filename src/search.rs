@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+/// Scores `candidate` against `query` using a case-insensitive subsequence match.
+///
+/// Every character of the query has to appear in the candidate in order.
+/// Consecutive matches and matches at the start of a word score higher, so
+/// "sp" ranks "Splash" above "Multisplash". Returns `None` when the candidate
+/// doesn't match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut previous_matched = false;
+    let mut previous = ' ';
+    let mut candidate_chars = candidate.chars().flat_map(char::to_lowercase);
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        loop {
+            let c = candidate_chars.next()?;
+            let word_start = !previous.is_alphanumeric();
+            previous = c;
+            if c == q {
+                score += 1;
+                if previous_matched {
+                    score += 2;
+                }
+                if word_start {
+                    score += 3;
+                }
+                previous_matched = true;
+                break;
+            }
+            previous_matched = false;
+        }
+    }
+
+    Some(score)
+}
+
+/// Filters `items` down to the ones matching `query`, best matches first.
+///
+/// An empty query keeps every item in its original order, and items with
+/// equal scores keep their relative order.
+pub fn filter<T>(
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> String,
+) -> Vec<T> {
+    let query = query.trim();
+    if query.is_empty() {
+        return items.into_iter().collect();
+    }
+
+    let mut matches: Vec<(u32, T)> = items
+        .into_iter()
+        .filter_map(|item| fuzzy_score(query, &key(&item)).map(|score| (score, item)))
+        .collect();
+    matches.sort_by(|a, b| b.0.cmp(&a.0));
+    matches.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequences_in_order() {
+        assert!(fuzzy_score("cll", "Cycle All").is_some());
+        assert!(fuzzy_score("rnbw", "Rainbow Chevron").is_some());
+        assert_eq!(fuzzy_score("lc", "Cycle"), None);
+        assert_eq!(fuzzy_score("xyz", "Splash"), None);
+        assert_eq!(fuzzy_score("splashes", "Splash"), None);
+    }
+
+    #[test]
+    fn ignores_case_and_spaces_in_the_query() {
+        assert_eq!(
+            fuzzy_score("SOLID", "solid color"),
+            fuzzy_score("solid", "Solid Color")
+        );
+        assert!(fuzzy_score("solid color", "SolidColor").is_some());
+    }
+
+    #[test]
+    fn word_starts_and_runs_score_higher() {
+        assert!(fuzzy_score("sp", "Splash") > fuzzy_score("sp", "Multisplash"));
+        assert!(fuzzy_score("rc", "Rainbow Chevron") > fuzzy_score("rc", "Per Key Colors"));
+        assert!(fuzzy_score("cyc", "Cycle All") > fuzzy_score("cyc", "Candy Cane"));
+    }
+
+    #[test]
+    fn filter_orders_best_first_and_keeps_ties_stable() {
+        let items = ["Multisplash", "Splash", "Spiral", "Disabled"];
+        assert_eq!(
+            filter("sp", items, |item| item.to_string()),
+            ["Splash", "Spiral", "Multisplash"]
+        );
+    }
+
+    #[test]
+    fn empty_query_keeps_everything() {
+        let items = ["b", "a", "c"];
+        assert_eq!(filter("  ", items, |item| item.to_string()), items);
+        assert!(filter("q", items, |item| item.to_string()).is_empty());
+    }
+}
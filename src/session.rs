@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// How long logind may take to answer.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Whether logind reports the applet's session as locked. Unknown states,
+/// e.g. outside a logind session, count as unlocked.
+pub async fn locked() -> bool {
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| String::from("auto"));
+    let output = Command::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .output();
+    match tokio::time::timeout(QUERY_TIMEOUT, output).await {
+        Ok(Ok(output)) => String::from_utf8_lossy(&output.stdout).trim() == "yes",
+        Ok(Err(err)) => {
+            eprintln!("failed to ask logind whether the session is locked: {err}");
+            false
+        }
+        Err(_) => false,
+    }
+}
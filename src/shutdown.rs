@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::Duration;
+
+use tokio::process::Command;
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::bus::{Event, EventBus};
+
+/// Why the applet is being stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// Only the panel or applet is going away; the session continues.
+    Restart,
+    /// The user is logging out.
+    Logout,
+    /// The system is shutting down or rebooting.
+    PowerOff,
+}
+
+impl ExitReason {
+    /// Asks systemd and logind what is going on. Anything that isn't clearly
+    /// the end of the session or the system counts as a restart.
+    pub async fn detect() -> Self {
+        if query("systemctl", &["is-system-running"]).await.as_deref() == Some("stopping") {
+            return Self::PowerOff;
+        }
+        let Ok(session) = std::env::var("XDG_SESSION_ID") else {
+            return Self::Restart;
+        };
+        let state = query(
+            "loginctl",
+            &["show-session", &session, "--property=State", "--value"],
+        )
+        .await;
+        if state.as_deref() == Some("closing") {
+            Self::Logout
+        } else {
+            Self::Restart
+        }
+    }
+}
+
+/// Runs a query command, returning its trimmed output.
+async fn query(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(2),
+        Command::new(program).args(args).output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Waits for the signals the panel sends when it stops or restarts its
+/// applets and publishes [`Event::ShutdownRequested`] with the reason.
+///
+/// Once this runs, those signals no longer terminate the process, so whoever
+/// handles the event has to exit.
+pub async fn run(bus: EventBus) {
+    let signals = [
+        SignalKind::terminate(),
+        SignalKind::interrupt(),
+        SignalKind::hangup(),
+    ]
+    .map(signal);
+    let [Ok(mut terminate), Ok(mut interrupt), Ok(mut hangup)] = signals else {
+        eprintln!("failed to install signal handlers, exit state won't be applied");
+        return;
+    };
+
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = interrupt.recv() => {}
+        _ = hangup.recv() => {}
+    }
+    bus.publish(Event::ShutdownRequested(ExitReason::detect().await));
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+use cosmic::iced::futures::FutureExt;
+
+use crate::bus::{Event, EventBus};
+
+/// Delay before the first restart, doubled after each quick failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// A task that ran at least this long counts as healthy again, resetting the
+/// backoff.
+const HEALTHY_AFTER: Duration = Duration::from_secs(60);
+
+/// Runs a background task forever, restarting it with exponential backoff
+/// whenever it panics or returns. Every failure is logged and published on the
+/// bus so the popup can show it.
+pub async fn supervise<F, Fut>(name: &'static str, bus: EventBus, mut task: F)
+where
+    F: FnMut(EventBus) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        let started = Instant::now();
+        let error = match AssertUnwindSafe(task(bus.clone())).catch_unwind().await {
+            Ok(()) => String::from("stopped"),
+            Err(panic) => format!("panicked: {}", panic_message(&*panic)),
+        };
+        if started.elapsed() >= HEALTHY_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+
+        eprintln!("{name} {error}, restarting in {}s", backoff.as_secs());
+        bus.publish(Event::TaskRestarted { task: name, error });
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{fs, io, path::Path};
+
+use crate::fl;
+
+/// Where a device sits in the USB tree and how fast its link is.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct UsbTopology {
+    /// Kernel name of the device, like `1-2.3` for port 3 of a hub on port 2
+    /// of bus 1.
+    pub port_path: String,
+    /// Negotiated speed in Mbit/s of the link towards the host, taken from
+    /// the keyboard's built-in hub when it has one.
+    pub speed: f64,
+    /// Product names of the hubs between the keyboard and the host, nearest
+    /// first, ending with the root hub. The built-in hub isn't listed.
+    pub hubs: Vec<String>,
+}
+
+impl UsbTopology {
+    /// Looks up the USB device behind a hidraw node like `/dev/hidraw3`.
+    pub fn from_hidraw(node: &str) -> io::Result<Self> {
+        Self::from_sysfs(Path::new("/sys"), node)
+    }
+
+    /// Like [`UsbTopology::from_hidraw`], with sysfs mounted at `sysfs`.
+    fn from_sysfs(sysfs: &Path, node: &str) -> io::Result<Self> {
+        let name = Path::new(node)
+            .file_name()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let hid = fs::canonicalize(sysfs.join("class/hidraw").join(name).join("device"))?;
+
+        let mut usb_devices = hid.ancestors().filter(|dir| dir.join("speed").is_file());
+        let device = usb_devices
+            .next()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        let mut hubs: Vec<&Path> = usb_devices.collect();
+
+        // A keyboard hardwired to a hub sits behind its own built-in hub,
+        // whose upstream link is the one the user's cable and ports decide.
+        let mut link = device;
+        if hubs.len() > 1 && read_attribute(device, "removable").is_ok_and(|r| r == "fixed") {
+            link = hubs.remove(0);
+        }
+
+        Ok(Self {
+            port_path: kernel_name(device),
+            speed: read_attribute(link, "speed")?
+                .parse()
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?,
+            hubs: hubs
+                .into_iter()
+                .map(|hub| read_attribute(hub, "product").unwrap_or_else(|_| kernel_name(hub)))
+                .collect(),
+        })
+    }
+
+    /// Whether a hub other than the host's root hub sits in between.
+    pub fn behind_hub(&self) -> bool {
+        self.hubs.len() > 1
+    }
+
+    /// USB name of the negotiated speed.
+    pub fn speed_label(&self) -> String {
+        match self.speed {
+            s if s <= 1.5 => fl!("usb-low-speed"),
+            s if s <= 12.0 => fl!("usb-full-speed"),
+            s if s <= 480.0 => fl!("usb-high-speed"),
+            _ => fl!("usb-super-speed"),
+        }
+    }
+}
+
+fn read_attribute(dir: &Path, name: &str) -> io::Result<String> {
+    Ok(fs::read_to_string(dir.join(name))?.trim().to_string())
+}
+
+fn kernel_name(dir: &Path) -> String {
+    dir.file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A throwaway sysfs tree with a keyboard at `1-1/1-1.1` on bus 1,
+    /// removed again on drop.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str, keyboard_removable: &str) -> Self {
+            let root =
+                std::env::temp_dir().join(format!("launch-usb-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            let bus = root.join("devices/usb1");
+            let hub = bus.join("1-1");
+            let keyboard = hub.join("1-1.1");
+            let hid = keyboard.join("1-1.1:1.0/0003:3384:0001.0001");
+            fs::create_dir_all(&hid).unwrap();
+            for (dir, speed, product) in [
+                (&bus, "480", "xHCI Host Controller"),
+                (&hub, "480", "USB2.1 Hub"),
+                (&keyboard, "12", "Launch Configurable Keyboard"),
+            ] {
+                fs::write(dir.join("speed"), format!("{speed}\n")).unwrap();
+                fs::write(dir.join("product"), format!("{product}\n")).unwrap();
+            }
+            fs::write(
+                keyboard.join("removable"),
+                format!("{keyboard_removable}\n"),
+            )
+            .unwrap();
+            let class = root.join("class/hidraw/hidraw3");
+            fs::create_dir_all(&class).unwrap();
+            std::os::unix::fs::symlink(&hid, class.join("device")).unwrap();
+            Self(root)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn built_in_hub_is_skipped() {
+        let fixture = Fixture::new("fixed", "fixed");
+        let topology = UsbTopology::from_sysfs(&fixture.0, "/dev/hidraw3").unwrap();
+        assert_eq!(topology.port_path, "1-1.1");
+        assert_eq!(topology.speed, 480.0);
+        assert_eq!(topology.hubs, ["xHCI Host Controller"]);
+        assert!(!topology.behind_hub());
+    }
+
+    #[test]
+    fn external_hub_is_listed() {
+        let fixture = Fixture::new("removable", "removable");
+        let topology = UsbTopology::from_sysfs(&fixture.0, "/dev/hidraw3").unwrap();
+        assert_eq!(topology.speed, 12.0);
+        assert_eq!(topology.hubs, ["USB2.1 Hub", "xHCI Host Controller"]);
+        assert!(topology.behind_hub());
+    }
+
+    #[test]
+    fn missing_node_is_an_error() {
+        let fixture = Fixture::new("missing", "removable");
+        assert!(UsbTopology::from_sysfs(&fixture.0, "/dev/hidraw9").is_err());
+    }
+
+    #[test]
+    fn speed_labels() {
+        for (speed, label) in [
+            (1.5, fl!("usb-low-speed")),
+            (12.0, fl!("usb-full-speed")),
+            (480.0, fl!("usb-high-speed")),
+            (5000.0, fl!("usb-super-speed")),
+            (10000.0, fl!("usb-super-speed")),
+        ] {
+            let topology = UsbTopology {
+                port_path: String::from("1-1"),
+                speed,
+                hubs: Vec::new(),
+            };
+            assert_eq!(topology.speed_label(), label, "{speed} Mbit/s");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+
+/// A slider that reports every position while dragging as a preview and sends
+/// `on_commit` once when released.
+///
+/// Previews are meant for cheap, live changes and should be rate limited with
+/// a [`Throttle`]; anything expensive like persisting belongs in the commit.
+pub fn preview_slider<'a, Message: Clone + 'a>(
+    range: RangeInclusive<u8>,
+    value: u8,
+    on_preview: impl Fn(u8) -> Message + 'a,
+    on_commit: Message,
+) -> Element<'a, Message> {
+    widget::slider(range, value, on_preview)
+        .on_release(on_commit)
+        .into()
+}
+
+/// A [`preview_slider`] with a field for typing an exact value and buttons
+/// nudging it by `step`.
+///
+/// `input` is the text being typed, if any; otherwise the field shows `value`.
+/// Typed values are clamped to `range`, and both they and nudges are sent
+/// through `on_set` as a finished change.
+#[allow(clippy::too_many_arguments)]
+pub fn numeric_slider<'a, Message: Clone + 'a>(
+    range: RangeInclusive<u8>,
+    value: u8,
+    step: u8,
+    input: Option<&'a str>,
+    on_preview: impl Fn(u8) -> Message + 'a,
+    on_commit: Message,
+    on_input: impl Fn(String) -> Message + 'a,
+    on_set: impl Fn(u8) -> Message + Clone + 'a,
+) -> Element<'a, Message> {
+    let (min, max) = (*range.start(), *range.end());
+    let text = input.map_or_else(|| value.to_string(), str::to_string);
+    let on_submit = on_set.clone();
+
+    widget::row()
+        .spacing(4)
+        .align_y(Alignment::Center)
+        .push(preview_slider(range, value, on_preview, on_commit))
+        .push(
+            widget::button::icon(widget::icon::from_name("list-remove-symbolic"))
+                .on_press(on_set(value.saturating_sub(step).max(min))),
+        )
+        .push(
+            widget::text_input("", text)
+                .width(Length::Fixed(56.0))
+                .on_input(on_input)
+                .on_submit(move |text| {
+                    let typed = text.trim().parse::<u32>().unwrap_or(u32::from(value));
+                    on_submit(typed.clamp(u32::from(min), u32::from(max)) as u8)
+                }),
+        )
+        .push(
+            widget::button::icon(widget::icon::from_name("list-add-symbolic"))
+                .on_press(on_set(value.saturating_add(step).min(max))),
+        )
+        .into()
+}
+
+/// Rate limiter for preview messages, so dragging a slider doesn't flood the
+/// EC with writes.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(50))
+    }
+}
+
+impl Throttle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: None,
+        }
+    }
+
+    /// Whether enough time passed since the last accepted call. Accepting a
+    /// call starts a new interval.
+    pub fn ready(&mut self) -> bool {
+        let now = Instant::now();
+        match self.last {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}